@@ -7,6 +7,8 @@ local client = require("snowcap.grpc.client").client
 ---@class snowcap.Snowcap
 local snowcap = {
     layer = require("snowcap.layer"),
+    output = require("snowcap.output"),
+    popup = require("snowcap.popup"),
     widget = require("snowcap.widget"),
 }
 
@@ -49,6 +49,41 @@ local snowcap_widget_v0alpha1_Font_Style = {
     STYLE_OBLIQUE = 3,
 }
 
+---@enum snowcap.popup.v0alpha1.Edge
+local snowcap_popup_v0alpha1_Edge = {
+    EDGE_UNSPECIFIED = 0,
+    EDGE_NONE = 1,
+    EDGE_TOP = 2,
+    EDGE_BOTTOM = 3,
+    EDGE_LEFT = 4,
+    EDGE_RIGHT = 5,
+    EDGE_TOP_LEFT = 6,
+    EDGE_BOTTOM_LEFT = 7,
+    EDGE_TOP_RIGHT = 8,
+    EDGE_BOTTOM_RIGHT = 9,
+}
+
+---@enum snowcap.popup.v0alpha1.PopupCloseReason
+local snowcap_popup_v0alpha1_PopupCloseReason = {
+    POPUP_CLOSE_REASON_UNSPECIFIED = 0,
+    POPUP_CLOSE_REASON_DISMISSED = 1,
+    POPUP_CLOSE_REASON_REQUEST = 2,
+    POPUP_CLOSE_REASON_PARENT_CLOSED = 3,
+}
+
+---@enum snowcap.output.v0alpha1.Transform
+local snowcap_output_v0alpha1_Transform = {
+    TRANSFORM_UNSPECIFIED = 0,
+    TRANSFORM_NORMAL = 1,
+    TRANSFORM_90 = 2,
+    TRANSFORM_180 = 3,
+    TRANSFORM_270 = 4,
+    TRANSFORM_FLIPPED = 5,
+    TRANSFORM_FLIPPED_90 = 6,
+    TRANSFORM_FLIPPED_180 = 7,
+    TRANSFORM_FLIPPED_270 = 8,
+}
+
 ---@enum snowcap.layer.v0alpha1.Anchor
 local snowcap_layer_v0alpha1_Anchor = {
     ANCHOR_UNSPECIFIED = 0,
@@ -79,20 +114,44 @@ local snowcap_layer_v0alpha1_Layer = {
     LAYER_OVERLAY = 4,
 }
 
+---@enum snowcap.layer.v0alpha1.CloseReason
+local snowcap_layer_v0alpha1_CloseReason = {
+    CLOSE_REASON_UNSPECIFIED = 0,
+    CLOSE_REASON_COMPOSITOR = 1,
+    CLOSE_REASON_REQUEST = 2,
+    CLOSE_REASON_OUTPUT_REMOVED = 3,
+    CLOSE_REASON_CLIENT_DISCONNECTED = 4,
+    CLOSE_REASON_TIMED_OUT = 5,
+}
+
+---@enum snowcap.layer.v0alpha1.HideReason
+local snowcap_layer_v0alpha1_HideReason = {
+    HIDE_REASON_UNSPECIFIED = 0,
+    HIDE_REASON_REQUEST = 1,
+    HIDE_REASON_TIMED_OUT = 2,
+}
+
 
 ---@class snowcap.input.v0alpha1.Modifiers
 ---@field shift boolean?
 ---@field ctrl boolean?
 ---@field alt boolean?
 ---@field super boolean?
+---@field caps_lock boolean?
+---@field num_lock boolean?
+---@field layout_index integer?
 
 ---@class snowcap.input.v0alpha1.KeyboardKeyRequest
 ---@field id integer?
+---@field repeat boolean?
 
 ---@class snowcap.input.v0alpha1.KeyboardKeyResponse
 ---@field key integer?
 ---@field modifiers snowcap.input.v0alpha1.Modifiers?
 ---@field pressed boolean?
+---@field text string?
+---@field keysym_name string?
+---@field repeat boolean?
 
 ---@class snowcap.input.v0alpha1.PointerButtonRequest
 ---@field id integer?
@@ -141,6 +200,8 @@ local snowcap_layer_v0alpha1_Layer = {
 ---@field row snowcap.widget.v0alpha1.Row?
 ---@field scrollable snowcap.widget.v0alpha1.Scrollable?
 ---@field container snowcap.widget.v0alpha1.Container?
+---@field button snowcap.widget.v0alpha1.Button?
+---@field text_input snowcap.widget.v0alpha1.TextInput?
 
 ---@class snowcap.widget.v0alpha1.Text
 ---@field text string?
@@ -202,6 +263,136 @@ local snowcap_layer_v0alpha1_Layer = {
 ---@field border_radius number?
 ---@field border_thickness number?
 ---@field border_color snowcap.widget.v0alpha1.Color?
+---@field interactive boolean?
+
+---@class snowcap.widget.v0alpha1.ButtonStyle
+---@field text_color snowcap.widget.v0alpha1.Color?
+---@field background_color snowcap.widget.v0alpha1.Color?
+---@field border_radius number?
+---@field border_thickness number?
+---@field border_color snowcap.widget.v0alpha1.Color?
+
+---@class snowcap.widget.v0alpha1.Button
+---@field child snowcap.widget.v0alpha1.WidgetDef?
+---@field padding snowcap.widget.v0alpha1.Padding?
+---@field width snowcap.widget.v0alpha1.Length?
+---@field height snowcap.widget.v0alpha1.Length?
+---@field style snowcap.widget.v0alpha1.ButtonStyle?
+---@field hovered_style snowcap.widget.v0alpha1.ButtonStyle?
+---@field pressed_style snowcap.widget.v0alpha1.ButtonStyle?
+---@field widget_id integer?
+
+---@class snowcap.widget.v0alpha1.TextInput
+---@field placeholder string?
+---@field value string?
+---@field font snowcap.widget.v0alpha1.Font?
+---@field pixels number?
+---@field padding snowcap.widget.v0alpha1.Padding?
+---@field width snowcap.widget.v0alpha1.Length?
+---@field password boolean?
+---@field widget_id integer?
+
+---@class snowcap.widget.v0alpha1.WatchWidgetEventsRequest
+---@field id integer?
+
+---@class snowcap.widget.v0alpha1.WatchWidgetEventsResponse
+---@field layer_id integer?
+---@field widget_id integer?
+---@field button_pressed snowcap.widget.v0alpha1.WatchWidgetEventsResponse.ButtonPressed?
+---@field text_changed snowcap.widget.v0alpha1.WatchWidgetEventsResponse.TextChanged?
+---@field text_submitted snowcap.widget.v0alpha1.WatchWidgetEventsResponse.TextSubmitted?
+
+---@class snowcap.widget.v0alpha1.WatchWidgetEventsResponse.ButtonPressed
+
+---@class snowcap.widget.v0alpha1.WatchWidgetEventsResponse.TextChanged
+---@field text string?
+
+---@class snowcap.widget.v0alpha1.WatchWidgetEventsResponse.TextSubmitted
+---@field text string?
+
+---@class snowcap.popup.v0alpha1.Rect
+---@field x integer?
+---@field y integer?
+---@field width integer?
+---@field height integer?
+
+---@class snowcap.popup.v0alpha1.NewPopupRequest
+---@field parent_id integer?
+---@field widget_def snowcap.widget.v0alpha1.WidgetDef?
+---@field width integer?
+---@field height integer?
+---@field anchor_rect snowcap.popup.v0alpha1.Rect?
+---@field anchor snowcap.popup.v0alpha1.Edge?
+---@field gravity snowcap.popup.v0alpha1.Edge?
+---@field constraint_adjustment integer?
+---@field offset_x integer?
+---@field offset_y integer?
+---@field grab boolean?
+
+---@class snowcap.popup.v0alpha1.NewPopupResponse
+---@field popup_id integer?
+
+---@class snowcap.popup.v0alpha1.ClosePopupRequest
+---@field popup_id integer?
+
+---@class snowcap.popup.v0alpha1.UpdatePopupRequest
+---@field popup_id integer?
+---@field widget_def snowcap.widget.v0alpha1.WidgetDef?
+
+---@class snowcap.popup.v0alpha1.WatchPopupRequest
+---@field popup_id integer?
+
+---@class snowcap.popup.v0alpha1.WatchPopupResponse
+---@field configured snowcap.popup.v0alpha1.WatchPopupResponse.Configured?
+---@field closed snowcap.popup.v0alpha1.WatchPopupResponse.Closed?
+
+---@class snowcap.popup.v0alpha1.WatchPopupResponse.Configured
+---@field x integer?
+---@field y integer?
+---@field width integer?
+---@field height integer?
+
+---@class snowcap.popup.v0alpha1.WatchPopupResponse.Closed
+---@field reason snowcap.popup.v0alpha1.PopupCloseReason?
+
+---@class snowcap.output.v0alpha1.Mode
+---@field width integer?
+---@field height integer?
+---@field refresh_rate integer?
+
+---@class snowcap.output.v0alpha1.OutputInfo
+---@field id integer?
+---@field name string?
+---@field description string?
+---@field make string?
+---@field model string?
+---@field logical_x integer?
+---@field logical_y integer?
+---@field logical_width integer?
+---@field logical_height integer?
+---@field scale integer?
+---@field transform snowcap.output.v0alpha1.Transform?
+---@field current_mode snowcap.output.v0alpha1.Mode?
+
+---@class snowcap.output.v0alpha1.ListOutputsRequest
+
+---@class snowcap.output.v0alpha1.ListOutputsResponse
+---@field outputs snowcap.output.v0alpha1.OutputInfo[]?
+
+---@class snowcap.output.v0alpha1.WatchOutputsRequest
+
+---@class snowcap.output.v0alpha1.WatchOutputsResponse
+---@field added snowcap.output.v0alpha1.OutputInfo?
+---@field changed snowcap.output.v0alpha1.OutputInfo?
+---@field removed snowcap.output.v0alpha1.OutputInfo?
+
+---@class snowcap.v0alpha1.Nothing
+
+---@class snowcap.layer.v0alpha1.Margin
+---@field top integer?
+---@field right integer?
+---@field bottom integer?
+---@field left integer?
 
 ---@class snowcap.layer.v0alpha1.NewLayerRequest
 ---@field widget_def snowcap.widget.v0alpha1.WidgetDef?
@@ -211,6 +402,35 @@ local snowcap_layer_v0alpha1_Layer = {
 ---@field keyboard_interactivity snowcap.layer.v0alpha1.KeyboardInteractivity?
 ---@field exclusive_zone integer?
 ---@field layer snowcap.layer.v0alpha1.Layer?
+---@field margin snowcap.layer.v0alpha1.Margin?
+---@field anchor_flags integer?
+---@field output_name string?
+---@field output_description string?
+---@field per_output snowcap.layer.v0alpha1.OutputFilter?
+---@field keep_on_disconnect boolean?
+---@field namespace string?
+---@field auto_size boolean?
+---@field timeout_ms integer?
+---@field pause_timeout_on_hover boolean?
+---@field hide_on_timeout boolean?
+---@field input_region snowcap.layer.v0alpha1.InputRegion?
+
+---@class snowcap.layer.v0alpha1.InputRegion
+---@field rects snowcap.layer.v0alpha1.InputRegion.Rects?
+---@field automatic google.protobuf.Empty?
+
+---@class snowcap.layer.v0alpha1.InputRegion.Rect
+---@field x integer?
+---@field y integer?
+---@field width integer?
+---@field height integer?
+
+---@class snowcap.layer.v0alpha1.InputRegion.Rects
+---@field rects snowcap.layer.v0alpha1.InputRegion.Rect[]?
+
+---@class snowcap.layer.v0alpha1.OutputFilter
+---@field names string[]?
+---@field description_contains string?
 
 ---@class snowcap.layer.v0alpha1.NewLayerResponse
 ---@field layer_id integer?
@@ -218,7 +438,105 @@ local snowcap_layer_v0alpha1_Layer = {
 ---@class snowcap.layer.v0alpha1.CloseRequest
 ---@field layer_id integer?
 
----@class snowcap.v0alpha1.Nothing
+---@class snowcap.layer.v0alpha1.HideRequest
+---@field layer_id integer?
+
+---@class snowcap.layer.v0alpha1.ShowRequest
+---@field layer_id integer?
+
+---@class snowcap.layer.v0alpha1.UpdateLayerRequest
+---@field layer_id integer?
+---@field widget_def snowcap.widget.v0alpha1.WidgetDef?
+
+---@class snowcap.layer.v0alpha1.SetLayerPropertiesRequest
+---@field layer_id integer?
+---@field width integer?
+---@field height integer?
+---@field anchor snowcap.layer.v0alpha1.Anchor?
+---@field keyboard_interactivity snowcap.layer.v0alpha1.KeyboardInteractivity?
+---@field exclusive_zone integer?
+---@field layer snowcap.layer.v0alpha1.Layer?
+---@field anchor_flags integer?
+---@field input_region snowcap.layer.v0alpha1.InputRegion?
+
+---@class snowcap.layer.v0alpha1.WatchLayerRequest
+---@field layer_id integer?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse
+---@field configured snowcap.layer.v0alpha1.WatchLayerResponse.Configured?
+---@field closed snowcap.layer.v0alpha1.WatchLayerResponse.Closed?
+---@field scale_changed snowcap.layer.v0alpha1.WatchLayerResponse.ScaleChanged?
+---@field output_entered snowcap.layer.v0alpha1.WatchLayerResponse.OutputEntered?
+---@field output_left snowcap.layer.v0alpha1.WatchLayerResponse.OutputLeft?
+---@field focus_gained snowcap.layer.v0alpha1.WatchLayerResponse.FocusGained?
+---@field focus_lost snowcap.layer.v0alpha1.WatchLayerResponse.FocusLost?
+---@field hidden snowcap.layer.v0alpha1.WatchLayerResponse.Hidden?
+---@field shown snowcap.layer.v0alpha1.WatchLayerResponse.Shown?
+---@field transform_changed snowcap.layer.v0alpha1.WatchLayerResponse.TransformChanged?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.Configured
+---@field width integer?
+---@field height integer?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.Closed
+---@field reason snowcap.layer.v0alpha1.CloseReason?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.ScaleChanged
+---@field scale integer?
+---@field fractional_scale number?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.OutputEntered
+---@field output_id integer?
+---@field output_name string?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.OutputLeft
+---@field output_id integer?
+---@field output_name string?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.Hidden
+---@field reason snowcap.layer.v0alpha1.HideReason?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.Shown
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.TransformChanged
+---@field transform snowcap.output.v0alpha1.Transform?
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.FocusGained
+
+---@class snowcap.layer.v0alpha1.WatchLayerResponse.FocusLost
+
+---@class snowcap.layer.v0alpha1.LayerInfo
+---@field layer_id integer?
+---@field namespace string?
+---@field width integer?
+---@field height integer?
+---@field scale integer?
+---@field anchor_flags integer?
+---@field layer snowcap.layer.v0alpha1.Layer?
+---@field exclusive_zone integer?
+---@field keyboard_interactivity snowcap.layer.v0alpha1.KeyboardInteractivity?
+---@field margin snowcap.layer.v0alpha1.Margin?
+---@field widget_def snowcap.widget.v0alpha1.WidgetDef?
+---@field group_id integer?
+---@field owner_client_id integer?
+---@field owner_pid integer?
+---@field keep_on_disconnect boolean?
+---@field auto_size boolean?
+---@field hidden boolean?
+---@field transform snowcap.output.v0alpha1.Transform?
+---@field outputs snowcap.output.v0alpha1.OutputInfo[]?
+---@field fractional_scale number?
+
+---@class snowcap.layer.v0alpha1.ListLayersRequest
+
+---@class snowcap.layer.v0alpha1.ListLayersResponse
+---@field layers snowcap.layer.v0alpha1.LayerInfo[]?
+
+---@class snowcap.layer.v0alpha1.GetLayerRequest
+---@field layer_id integer?
+
+---@class snowcap.layer.v0alpha1.GetLayerResponse
+---@field layer snowcap.layer.v0alpha1.LayerInfo?
 
 
 local snowcap = {}
@@ -247,22 +565,80 @@ snowcap.widget.v0alpha1.ScrollableDirection = {}
 snowcap.widget.v0alpha1.ScrollableProperties = {}
 snowcap.widget.v0alpha1.Scrollable = {}
 snowcap.widget.v0alpha1.Container = {}
+snowcap.widget.v0alpha1.ButtonStyle = {}
+snowcap.widget.v0alpha1.Button = {}
+snowcap.widget.v0alpha1.TextInput = {}
+snowcap.widget.v0alpha1.WatchWidgetEventsRequest = {}
+snowcap.widget.v0alpha1.WatchWidgetEventsResponse = {}
+snowcap.widget.v0alpha1.WatchWidgetEventsResponse.ButtonPressed = {}
+snowcap.widget.v0alpha1.WatchWidgetEventsResponse.TextChanged = {}
+snowcap.widget.v0alpha1.WatchWidgetEventsResponse.TextSubmitted = {}
+snowcap.popup = {}
+snowcap.popup.v0alpha1 = {}
+snowcap.popup.v0alpha1.Rect = {}
+snowcap.popup.v0alpha1.NewPopupRequest = {}
+snowcap.popup.v0alpha1.NewPopupResponse = {}
+snowcap.popup.v0alpha1.ClosePopupRequest = {}
+snowcap.popup.v0alpha1.UpdatePopupRequest = {}
+snowcap.popup.v0alpha1.WatchPopupRequest = {}
+snowcap.popup.v0alpha1.WatchPopupResponse = {}
+snowcap.popup.v0alpha1.WatchPopupResponse.Configured = {}
+snowcap.popup.v0alpha1.WatchPopupResponse.Closed = {}
+snowcap.output = {}
+snowcap.output.v0alpha1 = {}
+snowcap.output.v0alpha1.Mode = {}
+snowcap.output.v0alpha1.OutputInfo = {}
+snowcap.output.v0alpha1.ListOutputsRequest = {}
+snowcap.output.v0alpha1.ListOutputsResponse = {}
+snowcap.output.v0alpha1.WatchOutputsRequest = {}
+snowcap.output.v0alpha1.WatchOutputsResponse = {}
+snowcap.v0alpha1 = {}
+snowcap.v0alpha1.Nothing = {}
 snowcap.layer = {}
 snowcap.layer.v0alpha1 = {}
+snowcap.layer.v0alpha1.Margin = {}
 snowcap.layer.v0alpha1.NewLayerRequest = {}
+snowcap.layer.v0alpha1.InputRegion = {}
+snowcap.layer.v0alpha1.InputRegion.Rect = {}
+snowcap.layer.v0alpha1.InputRegion.Rects = {}
+snowcap.layer.v0alpha1.OutputFilter = {}
 snowcap.layer.v0alpha1.NewLayerResponse = {}
 snowcap.layer.v0alpha1.CloseRequest = {}
-snowcap.v0alpha1 = {}
-snowcap.v0alpha1.Nothing = {}
+snowcap.layer.v0alpha1.HideRequest = {}
+snowcap.layer.v0alpha1.ShowRequest = {}
+snowcap.layer.v0alpha1.UpdateLayerRequest = {}
+snowcap.layer.v0alpha1.SetLayerPropertiesRequest = {}
+snowcap.layer.v0alpha1.WatchLayerRequest = {}
+snowcap.layer.v0alpha1.WatchLayerResponse = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.Configured = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.Closed = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.ScaleChanged = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.OutputEntered = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.OutputLeft = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.Hidden = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.Shown = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.TransformChanged = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.FocusGained = {}
+snowcap.layer.v0alpha1.WatchLayerResponse.FocusLost = {}
+snowcap.layer.v0alpha1.LayerInfo = {}
+snowcap.layer.v0alpha1.ListLayersRequest = {}
+snowcap.layer.v0alpha1.ListLayersResponse = {}
+snowcap.layer.v0alpha1.GetLayerRequest = {}
+snowcap.layer.v0alpha1.GetLayerResponse = {}
 
 snowcap.widget.v0alpha1.Alignment = snowcap_widget_v0alpha1_Alignment
 snowcap.widget.v0alpha1.ScrollableAlignment = snowcap_widget_v0alpha1_ScrollableAlignment
 snowcap.widget.v0alpha1.Font.Weight = snowcap_widget_v0alpha1_Font_Weight
 snowcap.widget.v0alpha1.Font.Stretch = snowcap_widget_v0alpha1_Font_Stretch
 snowcap.widget.v0alpha1.Font.Style = snowcap_widget_v0alpha1_Font_Style
+snowcap.popup.v0alpha1.Edge = snowcap_popup_v0alpha1_Edge
+snowcap.popup.v0alpha1.PopupCloseReason = snowcap_popup_v0alpha1_PopupCloseReason
+snowcap.output.v0alpha1.Transform = snowcap_output_v0alpha1_Transform
 snowcap.layer.v0alpha1.Anchor = snowcap_layer_v0alpha1_Anchor
 snowcap.layer.v0alpha1.KeyboardInteractivity = snowcap_layer_v0alpha1_KeyboardInteractivity
 snowcap.layer.v0alpha1.Layer = snowcap_layer_v0alpha1_Layer
+snowcap.layer.v0alpha1.CloseReason = snowcap_layer_v0alpha1_CloseReason
+snowcap.layer.v0alpha1.HideReason = snowcap_layer_v0alpha1_HideReason
 
 snowcap.input.v0alpha1.InputService = {}
 snowcap.input.v0alpha1.InputService.KeyboardKey = {}
@@ -275,6 +651,44 @@ snowcap.input.v0alpha1.InputService.PointerButton.service = "snowcap.input.v0alp
 snowcap.input.v0alpha1.InputService.PointerButton.method = "PointerButton"
 snowcap.input.v0alpha1.InputService.PointerButton.request = ".snowcap.input.v0alpha1.PointerButtonRequest"
 snowcap.input.v0alpha1.InputService.PointerButton.response = ".snowcap.input.v0alpha1.PointerButtonResponse"
+snowcap.widget.v0alpha1.WidgetService = {}
+snowcap.widget.v0alpha1.WidgetService.WatchWidgetEvents = {}
+snowcap.widget.v0alpha1.WidgetService.WatchWidgetEvents.service = "snowcap.widget.v0alpha1.WidgetService"
+snowcap.widget.v0alpha1.WidgetService.WatchWidgetEvents.method = "WatchWidgetEvents"
+snowcap.widget.v0alpha1.WidgetService.WatchWidgetEvents.request = ".snowcap.widget.v0alpha1.WatchWidgetEventsRequest"
+snowcap.widget.v0alpha1.WidgetService.WatchWidgetEvents.response = ".snowcap.widget.v0alpha1.WatchWidgetEventsResponse"
+snowcap.popup.v0alpha1.PopupService = {}
+snowcap.popup.v0alpha1.PopupService.NewPopup = {}
+snowcap.popup.v0alpha1.PopupService.NewPopup.service = "snowcap.popup.v0alpha1.PopupService"
+snowcap.popup.v0alpha1.PopupService.NewPopup.method = "NewPopup"
+snowcap.popup.v0alpha1.PopupService.NewPopup.request = ".snowcap.popup.v0alpha1.NewPopupRequest"
+snowcap.popup.v0alpha1.PopupService.NewPopup.response = ".snowcap.popup.v0alpha1.NewPopupResponse"
+snowcap.popup.v0alpha1.PopupService.Close = {}
+snowcap.popup.v0alpha1.PopupService.Close.service = "snowcap.popup.v0alpha1.PopupService"
+snowcap.popup.v0alpha1.PopupService.Close.method = "Close"
+snowcap.popup.v0alpha1.PopupService.Close.request = ".snowcap.popup.v0alpha1.ClosePopupRequest"
+snowcap.popup.v0alpha1.PopupService.Close.response = ".google.protobuf.Empty"
+snowcap.popup.v0alpha1.PopupService.UpdatePopup = {}
+snowcap.popup.v0alpha1.PopupService.UpdatePopup.service = "snowcap.popup.v0alpha1.PopupService"
+snowcap.popup.v0alpha1.PopupService.UpdatePopup.method = "UpdatePopup"
+snowcap.popup.v0alpha1.PopupService.UpdatePopup.request = ".snowcap.popup.v0alpha1.UpdatePopupRequest"
+snowcap.popup.v0alpha1.PopupService.UpdatePopup.response = ".google.protobuf.Empty"
+snowcap.popup.v0alpha1.PopupService.WatchPopup = {}
+snowcap.popup.v0alpha1.PopupService.WatchPopup.service = "snowcap.popup.v0alpha1.PopupService"
+snowcap.popup.v0alpha1.PopupService.WatchPopup.method = "WatchPopup"
+snowcap.popup.v0alpha1.PopupService.WatchPopup.request = ".snowcap.popup.v0alpha1.WatchPopupRequest"
+snowcap.popup.v0alpha1.PopupService.WatchPopup.response = ".snowcap.popup.v0alpha1.WatchPopupResponse"
+snowcap.output.v0alpha1.OutputService = {}
+snowcap.output.v0alpha1.OutputService.ListOutputs = {}
+snowcap.output.v0alpha1.OutputService.ListOutputs.service = "snowcap.output.v0alpha1.OutputService"
+snowcap.output.v0alpha1.OutputService.ListOutputs.method = "ListOutputs"
+snowcap.output.v0alpha1.OutputService.ListOutputs.request = ".snowcap.output.v0alpha1.ListOutputsRequest"
+snowcap.output.v0alpha1.OutputService.ListOutputs.response = ".snowcap.output.v0alpha1.ListOutputsResponse"
+snowcap.output.v0alpha1.OutputService.WatchOutputs = {}
+snowcap.output.v0alpha1.OutputService.WatchOutputs.service = "snowcap.output.v0alpha1.OutputService"
+snowcap.output.v0alpha1.OutputService.WatchOutputs.method = "WatchOutputs"
+snowcap.output.v0alpha1.OutputService.WatchOutputs.request = ".snowcap.output.v0alpha1.WatchOutputsRequest"
+snowcap.output.v0alpha1.OutputService.WatchOutputs.response = ".snowcap.output.v0alpha1.WatchOutputsResponse"
 snowcap.layer.v0alpha1.LayerService = {}
 snowcap.layer.v0alpha1.LayerService.NewLayer = {}
 snowcap.layer.v0alpha1.LayerService.NewLayer.service = "snowcap.layer.v0alpha1.LayerService"
@@ -286,6 +700,41 @@ snowcap.layer.v0alpha1.LayerService.Close.service = "snowcap.layer.v0alpha1.Laye
 snowcap.layer.v0alpha1.LayerService.Close.method = "Close"
 snowcap.layer.v0alpha1.LayerService.Close.request = ".snowcap.layer.v0alpha1.CloseRequest"
 snowcap.layer.v0alpha1.LayerService.Close.response = ".google.protobuf.Empty"
+snowcap.layer.v0alpha1.LayerService.Hide = {}
+snowcap.layer.v0alpha1.LayerService.Hide.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.Hide.method = "Hide"
+snowcap.layer.v0alpha1.LayerService.Hide.request = ".snowcap.layer.v0alpha1.HideRequest"
+snowcap.layer.v0alpha1.LayerService.Hide.response = ".google.protobuf.Empty"
+snowcap.layer.v0alpha1.LayerService.Show = {}
+snowcap.layer.v0alpha1.LayerService.Show.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.Show.method = "Show"
+snowcap.layer.v0alpha1.LayerService.Show.request = ".snowcap.layer.v0alpha1.ShowRequest"
+snowcap.layer.v0alpha1.LayerService.Show.response = ".google.protobuf.Empty"
+snowcap.layer.v0alpha1.LayerService.UpdateLayer = {}
+snowcap.layer.v0alpha1.LayerService.UpdateLayer.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.UpdateLayer.method = "UpdateLayer"
+snowcap.layer.v0alpha1.LayerService.UpdateLayer.request = ".snowcap.layer.v0alpha1.UpdateLayerRequest"
+snowcap.layer.v0alpha1.LayerService.UpdateLayer.response = ".google.protobuf.Empty"
+snowcap.layer.v0alpha1.LayerService.SetLayerProperties = {}
+snowcap.layer.v0alpha1.LayerService.SetLayerProperties.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.SetLayerProperties.method = "SetLayerProperties"
+snowcap.layer.v0alpha1.LayerService.SetLayerProperties.request = ".snowcap.layer.v0alpha1.SetLayerPropertiesRequest"
+snowcap.layer.v0alpha1.LayerService.SetLayerProperties.response = ".google.protobuf.Empty"
+snowcap.layer.v0alpha1.LayerService.WatchLayer = {}
+snowcap.layer.v0alpha1.LayerService.WatchLayer.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.WatchLayer.method = "WatchLayer"
+snowcap.layer.v0alpha1.LayerService.WatchLayer.request = ".snowcap.layer.v0alpha1.WatchLayerRequest"
+snowcap.layer.v0alpha1.LayerService.WatchLayer.response = ".snowcap.layer.v0alpha1.WatchLayerResponse"
+snowcap.layer.v0alpha1.LayerService.ListLayers = {}
+snowcap.layer.v0alpha1.LayerService.ListLayers.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.ListLayers.method = "ListLayers"
+snowcap.layer.v0alpha1.LayerService.ListLayers.request = ".snowcap.layer.v0alpha1.ListLayersRequest"
+snowcap.layer.v0alpha1.LayerService.ListLayers.response = ".snowcap.layer.v0alpha1.ListLayersResponse"
+snowcap.layer.v0alpha1.LayerService.GetLayer = {}
+snowcap.layer.v0alpha1.LayerService.GetLayer.service = "snowcap.layer.v0alpha1.LayerService"
+snowcap.layer.v0alpha1.LayerService.GetLayer.method = "GetLayer"
+snowcap.layer.v0alpha1.LayerService.GetLayer.request = ".snowcap.layer.v0alpha1.GetLayerRequest"
+snowcap.layer.v0alpha1.LayerService.GetLayer.response = ".snowcap.layer.v0alpha1.GetLayerResponse"
 
 return {
     snowcap = snowcap,
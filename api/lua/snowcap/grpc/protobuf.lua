@@ -16,6 +16,8 @@ function protobuf.build_protos()
     local proto_file_paths = {
         SNOWCAP_PROTO_DIR .. "/snowcap/input/" .. version .. "/input.proto",
         SNOWCAP_PROTO_DIR .. "/snowcap/layer/" .. version .. "/layer.proto",
+        SNOWCAP_PROTO_DIR .. "/snowcap/output/" .. version .. "/output.proto",
+        SNOWCAP_PROTO_DIR .. "/snowcap/popup/" .. version .. "/popup.proto",
         SNOWCAP_PROTO_DIR .. "/snowcap/widget/" .. version .. "/widget.proto",
         SNOWCAP_PROTO_DIR .. "/google/protobuf/empty.proto",
     }
@@ -11,5 +11,8 @@ local input = {
 ---@field ctrl boolean
 ---@field alt boolean
 ---@field super boolean
+---@field caps_lock boolean
+---@field num_lock boolean
+---@field layout_index integer The index of the active keyboard layout
 
 return input
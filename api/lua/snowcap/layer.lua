@@ -8,6 +8,7 @@ local layer_service = require("snowcap.grpc.defs").snowcap.layer.v0alpha1.LayerS
 local input_service = require("snowcap.grpc.defs").snowcap.input.v0alpha1.InputService
 
 local widget = require("snowcap.widget")
+local popup = require("snowcap.popup")
 
 ---@class Layer
 local layer = {}
@@ -28,6 +29,13 @@ function layer_handle.new(id)
     return self
 end
 
+---The edges a layer surface is anchored to.
+---
+---Anchors can be combined by passing a list of them. Anchoring to two opposite edges
+---with a size of 0 on that axis will let the compositor stretch the layer across it,
+---for example `{ anchor.TOP, anchor.LEFT, anchor.RIGHT }` with a width of 0
+---for a full-width top bar.
+---
 ---@enum snowcap.Anchor
 local anchor = {
     TOP = 1,
@@ -40,6 +48,40 @@ local anchor = {
     BOTTOM_RIGHT = 8,
 }
 
+---The `wlr-layer-shell` edge flags each anchor is made of.
+local anchor_edge_flags = {
+    [anchor.TOP] = { 1 },
+    [anchor.BOTTOM] = { 2 },
+    [anchor.LEFT] = { 4 },
+    [anchor.RIGHT] = { 8 },
+    [anchor.TOP_LEFT] = { 1, 4 },
+    [anchor.TOP_RIGHT] = { 1, 8 },
+    [anchor.BOTTOM_LEFT] = { 2, 4 },
+    [anchor.BOTTOM_RIGHT] = { 2, 8 },
+}
+
+---@param anchors snowcap.Anchor|snowcap.Anchor[]
+---@return integer
+local function anchor_to_api(anchors)
+    if type(anchors) ~= "table" then
+        anchors = { anchors }
+    end
+
+    local edges = {}
+    for _, each in ipairs(anchors) do
+        for _, flag in ipairs(anchor_edge_flags[each] or {}) do
+            edges[flag] = true
+        end
+    end
+
+    local flags = 0
+    for flag in pairs(edges) do
+        flags = flags + flag
+    end
+
+    return flags
+end
+
 ---@enum snowcap.KeyboardInteractivity
 local keyboard_interactivity = {
     NONE = 1,
@@ -55,6 +97,33 @@ local zlayer = {
     OVERLAY = 4,
 }
 
+---Why a layer was closed.
+---@enum snowcap.CloseReason
+local close_reason = {
+    UNKNOWN = 0,
+    ---The compositor closed the layer
+    COMPOSITOR = 1,
+    ---A client or the layer's widgets asked for the layer to be closed
+    REQUEST = 2,
+    ---The output a layer group instance was on was disconnected
+    ---or no longer matches the group's filter
+    OUTPUT_REMOVED = 3,
+    ---The client that created the layer disconnected
+    CLIENT_DISCONNECTED = 4,
+    ---The layer's timeout ran out
+    TIMED_OUT = 5,
+}
+
+---Why a layer was hidden.
+---@enum snowcap.HideReason
+local hide_reason = {
+    UNKNOWN = 0,
+    ---A client asked for the layer to be hidden
+    REQUEST = 1,
+    ---The layer's timeout ran out
+    TIMED_OUT = 2,
+}
+
 ---@alias snowcap.ExclusiveZone
 ---| integer
 ---| "respect"
@@ -74,14 +143,77 @@ local function exclusive_zone_to_api(zone)
     return -1
 end
 
+---A rectangle in surface-local logical coordinates.
+---@class snowcap.Rect
+---@field x integer
+---@field y integer
+---@field width integer
+---@field height integer
+
+---The part of a layer that accepts pointer input. Pointer input outside of it
+---goes to whatever is beneath the layer.
+---
+---- A list of rectangles: only these accept input
+---- "automatic": only the bounds of containers marked `interactive` accept input,
+---  recomputed whenever the layer's widgets are laid out
+---- "full": the whole layer accepts input
+---
+---@alias snowcap.InputRegion
+---| snowcap.Rect[]
+---| "automatic"
+---| "full"
+
+---@param region snowcap.InputRegion
+---@return snowcap.layer.v0alpha1.InputRegion
+local function input_region_to_api(region)
+    if region == "automatic" then
+        return { automatic = {} }
+    end
+
+    if region == "full" then
+        return {}
+    end
+
+    return { rects = { rects = region } }
+end
+
+---The distance a layer surface keeps from the edges it is anchored to.
+---@class snowcap.Margin
+---@field top integer?
+---@field right integer?
+---@field bottom integer?
+---@field left integer?
+
+---Which output a layer surface should be placed on.
+---Only one of the fields should be set.
+---@class snowcap.OutputSelector
+---@field name string? The output's name, like "DP-1"
+---@field description string? The output's human-readable description
+---@field per_output snowcap.OutputFilter? Create one instance of the layer on every output matching this filter, including outputs connected later. The returned handle controls every instance at once.
+
+---Which outputs a layer created with `per_output` is placed on.
+---An empty filter matches every output.
+---@class snowcap.OutputFilter
+---@field names string[]? Only match outputs with one of these names
+---@field description_contains string? Only match outputs whose description contains this string
+
 ---@class LayerArgs
 ---@field widget snowcap.WidgetDef
 ---@field width integer
 ---@field height integer
----@field anchor snowcap.Anchor?
+---@field anchor (snowcap.Anchor|snowcap.Anchor[])?
 ---@field keyboard_interactivity snowcap.KeyboardInteractivity
 ---@field exclusive_zone snowcap.ExclusiveZone
 ---@field layer snowcap.ZLayer
+---@field margin snowcap.Margin?
+---@field output snowcap.OutputSelector? The output to place the layer on. If nil, the compositor decides.
+---@field keep_on_disconnect boolean? Keep the layer open after this client disconnects. Defaults to false.
+---@field namespace string? The namespace compositors can use to apply rules to the layer. Defaults to "snowcap".
+---@field timeout integer? Close the layer by itself after this many milliseconds
+---@field pause_timeout_on_hover boolean? Pause the timeout while the pointer is over the layer
+---@field hide_on_timeout boolean? Hide the layer instead of closing it when the timeout runs out. The countdown starts over when the layer is shown again.
+---@field auto_size boolean? Size the layer to fit its widgets, re-measuring whenever they change. `width` and `height` become the maximum size.
+---@field input_region snowcap.InputRegion? The part of the layer that accepts pointer input. Defaults to the whole layer.
 
 ---@param args LayerArgs
 ---@return LayerHandle|nil handle A handle to the layer surface, or nil if an error occurred.
@@ -92,9 +224,20 @@ function layer.new_widget(args)
         exclusive_zone = exclusive_zone_to_api(args.exclusive_zone),
         width = args.width,
         height = args.height,
-        anchor = args.anchor,
+        anchor_flags = args.anchor and anchor_to_api(args.anchor),
         keyboard_interactivity = args.keyboard_interactivity,
         widget_def = widget.widget_def_into_api(args.widget),
+        margin = args.margin --[[@as snowcap.layer.v0alpha1.Margin]],
+        output_name = args.output and args.output.name,
+        output_description = args.output and args.output.description,
+        per_output = args.output and args.output.per_output --[[@as snowcap.layer.v0alpha1.OutputFilter]],
+        keep_on_disconnect = args.keep_on_disconnect,
+        namespace = args.namespace,
+        auto_size = args.auto_size,
+        input_region = args.input_region and input_region_to_api(args.input_region),
+        timeout_ms = args.timeout,
+        pause_timeout_on_hover = args.pause_timeout_on_hover,
+        hide_on_timeout = args.hide_on_timeout,
     }
 
     local response, err = client:unary_request(layer_service.NewLayer, request)
@@ -114,11 +257,39 @@ function layer.new_widget(args)
     return layer_handle.new(response.layer_id)
 end
 
----@param on_press fun(mods: snowcap.input.Modifiers, key: snowcap.Key)
-function LayerHandle:on_key_press(on_press)
+---@param mods snowcap.input.v0alpha1.Modifiers?
+---@return snowcap.input.Modifiers
+local function modifiers_from_api(mods)
+    mods = mods or {}
+
+    ---@type snowcap.input.Modifiers
+    return {
+        shift = mods.shift or false,
+        ctrl = mods.ctrl or false,
+        alt = mods.alt or false,
+        super = mods.super or false,
+        caps_lock = mods.caps_lock or false,
+        num_lock = mods.num_lock or false,
+        layout_index = mods.layout_index or 0,
+    }
+end
+
+---Do something on key press.
+---
+---If `with_repeat` is true, this is also called repeatedly while a key is held down,
+---with `repeat` set to true.
+---
+---`text` is the text the key press produces, with compose sequences applied.
+---It is nil if the key produces no text or is part of an unfinished compose sequence,
+---and may be a control character, like "\r" for Return.
+---`name` is the name of the keysym, like "Return" or "a".
+---
+---@param on_press fun(mods: snowcap.input.Modifiers, key: snowcap.Key, text: string?, name: string, repeat: boolean)
+---@param with_repeat boolean? Whether to receive repeated presses of held keys. Defaults to false.
+function LayerHandle:on_key_press(on_press, with_repeat)
     local err = client:server_streaming_request(
         input_service.KeyboardKey,
-        { id = self.id },
+        { id = self.id, ["repeat"] = with_repeat or false },
         function(response)
             ---@cast response snowcap.input.v0alpha1.KeyboardKeyResponse
 
@@ -126,15 +297,42 @@ function LayerHandle:on_key_press(on_press)
                 return
             end
 
-            local mods = response.modifiers or {}
-            mods.shift = mods.shift or false
-            mods.ctrl = mods.ctrl or false
-            mods.alt = mods.alt or false
-            mods.super = mods.super or false
+            on_press(
+                modifiers_from_api(response.modifiers),
+                response.key or 0,
+                response.text,
+                response.keysym_name or "",
+                response["repeat"] or false
+            )
+        end
+    )
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Do something on key release.
+---
+---`name` is the name of the keysym, like "Return" or "a".
+---
+---@param on_release fun(mods: snowcap.input.Modifiers, key: snowcap.Key, name: string)
+function LayerHandle:on_key_release(on_release)
+    local err = client:server_streaming_request(
+        input_service.KeyboardKey,
+        { id = self.id },
+        function(response)
+            ---@cast response snowcap.input.v0alpha1.KeyboardKeyResponse
 
-            ---@cast mods snowcap.input.Modifiers
+            if response.pressed then
+                return
+            end
 
-            on_press(mods, response.key or 0)
+            on_release(
+                modifiers_from_api(response.modifiers),
+                response.key or 0,
+                response.keysym_name or ""
+            )
         end
     )
 
@@ -143,6 +341,226 @@ function LayerHandle:on_key_press(on_press)
     end
 end
 
+---Replace this layer's widget tree with a new one.
+---
+---The layer surface is kept alive, so this can be used to update
+---frequently changing content without the layer flickering.
+---
+---@param widget_def snowcap.WidgetDef
+function LayerHandle:update(widget_def)
+    local _, err = client:unary_request(layer_service.UpdateLayer, {
+        layer_id = self.id,
+        widget_def = widget.widget_def_into_api(widget_def),
+    })
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Create a popup attached to this layer, like a menu or tooltip.
+---
+---The popup's position is relative to this layer.
+---
+---@param args PopupArgs
+---@return PopupHandle|nil handle A handle to the popup, or nil if an error occurred.
+function LayerHandle:new_popup(args)
+    return popup.new_popup(self.id, args)
+end
+
+---@class LayerProperties
+---@field width integer? The new width, or the maximum width if the layer is auto-sized
+---@field height integer? The new height, or the maximum height if the layer is auto-sized
+---@field anchor (snowcap.Anchor|snowcap.Anchor[])?
+---@field keyboard_interactivity snowcap.KeyboardInteractivity?
+---@field exclusive_zone snowcap.ExclusiveZone?
+---@field layer snowcap.ZLayer?
+---@field input_region snowcap.InputRegion?
+
+---Change properties of this layer.
+---
+---Properties that are not specified will not be changed.
+---Changing the z-layer requires version 2 of `wlr-layer-shell`.
+---
+---@param properties LayerProperties
+function LayerHandle:set_properties(properties)
+    ---@type snowcap.layer.v0alpha1.SetLayerPropertiesRequest
+    local request = {
+        layer_id = self.id,
+        width = properties.width,
+        height = properties.height,
+        anchor_flags = properties.anchor and anchor_to_api(properties.anchor),
+        keyboard_interactivity = properties.keyboard_interactivity,
+        exclusive_zone = properties.exclusive_zone
+            and exclusive_zone_to_api(properties.exclusive_zone),
+        layer = properties.layer,
+        input_region = properties.input_region and input_region_to_api(properties.input_region),
+    }
+
+    local _, err = client:unary_request(layer_service.SetLayerProperties, request)
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Callbacks for layer events. If the handle refers to a layer group,
+---events from every instance are received.
+---
+---@class snowcap.LayerEventHandlers
+---@field configured fun(width: integer, height: integer)? The compositor configured the layer with a new size
+---@field closed fun(reason: snowcap.CloseReason)? The layer was closed
+---@field scale_changed fun(scale: number)? The layer's scale changed. It may be fractional
+---@field output_entered fun(output_id: integer, output_name: string?)? The layer entered an output
+---@field output_left fun(output_id: integer, output_name: string?)? The layer left an output
+---@field focus_gained fun()? The layer gained keyboard focus
+---@field focus_lost fun()? The layer lost keyboard focus
+---@field hidden fun(reason: snowcap.HideReason)? The layer was hidden
+---@field shown fun()? The layer was shown again after being hidden
+---@field transform_changed fun(transform: snowcap.output.v0alpha1.Transform)? The transform of the output the layer is on changed
+
+---Do something whenever anything happens to this layer.
+---
+---@param handlers snowcap.LayerEventHandlers
+function LayerHandle:on_event(handlers)
+    local err = client:server_streaming_request(
+        layer_service.WatchLayer,
+        { layer_id = self.id },
+        function(response)
+            ---@cast response snowcap.layer.v0alpha1.WatchLayerResponse
+
+            if response.configured and handlers.configured then
+                handlers.configured(response.configured.width or 0, response.configured.height or 0)
+            elseif response.closed and handlers.closed then
+                handlers.closed(response.closed.reason or close_reason.UNKNOWN)
+            elseif response.scale_changed and handlers.scale_changed then
+                handlers.scale_changed(
+                    response.scale_changed.fractional_scale or response.scale_changed.scale or 1
+                )
+            elseif response.output_entered and handlers.output_entered then
+                handlers.output_entered(
+                    response.output_entered.output_id or 0,
+                    response.output_entered.output_name
+                )
+            elseif response.output_left and handlers.output_left then
+                handlers.output_left(
+                    response.output_left.output_id or 0,
+                    response.output_left.output_name
+                )
+            elseif response.focus_gained and handlers.focus_gained then
+                handlers.focus_gained()
+            elseif response.focus_lost and handlers.focus_lost then
+                handlers.focus_lost()
+            elseif response.hidden and handlers.hidden then
+                handlers.hidden(response.hidden.reason or hide_reason.UNKNOWN)
+            elseif response.shown and handlers.shown then
+                handlers.shown()
+            elseif response.transform_changed and handlers.transform_changed then
+                handlers.transform_changed(response.transform_changed.transform or 0)
+            end
+        end
+    )
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Do something when the compositor configures this layer with a new size.
+---
+---This is useful for layers anchored to opposite edges, whose size
+---is chosen by the compositor.
+---
+---@param on_configure fun(width: integer, height: integer)
+function LayerHandle:on_configure(on_configure)
+    self:on_event({ configured = on_configure })
+end
+
+---Do something when this layer is closed.
+---
+---After this, the handle no longer refers to a live layer.
+---
+---@param on_close fun(reason: snowcap.CloseReason)
+function LayerHandle:on_close(on_close)
+    self:on_event({ closed = on_close })
+end
+
+---A description of a live layer.
+---@class snowcap.LayerInfo : snowcap.layer.v0alpha1.LayerInfo
+---@field handle LayerHandle A handle to the layer
+
+---@param info snowcap.layer.v0alpha1.LayerInfo
+---@return snowcap.LayerInfo
+local function layer_info_from_api(info)
+    ---@cast info snowcap.LayerInfo
+    info.handle = layer_handle.new(info.layer_id)
+    return info
+end
+
+---Get information about all live layers, including ones created by other clients.
+---
+---@return snowcap.LayerInfo[]
+function layer.layers()
+    local response, err = client:unary_request(layer_service.ListLayers, {})
+
+    if err then
+        log:error(err)
+        return {}
+    end
+
+    ---@cast response snowcap.layer.v0alpha1.ListLayersResponse
+
+    local infos = {}
+    for _, info in ipairs(response.layers or {}) do
+        table.insert(infos, layer_info_from_api(info))
+    end
+
+    return infos
+end
+
+---Get information about this layer.
+---
+---Returns nil if the layer no longer exists or this handle refers to a layer group.
+---
+---@return snowcap.LayerInfo|nil
+function LayerHandle:info()
+    local response, err = client:unary_request(layer_service.GetLayer, { layer_id = self.id })
+
+    if err then
+        log:error(err)
+        return nil
+    end
+
+    ---@cast response snowcap.layer.v0alpha1.GetLayerResponse
+
+    if not response.layer then
+        return nil
+    end
+
+    return layer_info_from_api(response.layer)
+end
+
+---Hide this layer.
+---
+---The layer's widgets keep their state, like scroll positions,
+---and are shown as they were when `show` is called.
+function LayerHandle:hide()
+    local _, err = client:unary_request(layer_service.Hide, { layer_id = self.id })
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Show this layer again after it was hidden.
+function LayerHandle:show()
+    local _, err = client:unary_request(layer_service.Show, { layer_id = self.id })
+
+    if err then
+        log:error(err)
+    end
+end
+
 function LayerHandle:close()
     local _, err = client:unary_request(layer_service.Close, { layer_id = self.id })
 
@@ -154,5 +572,7 @@ end
 layer.anchor = anchor
 layer.keyboard_interactivity = keyboard_interactivity
 layer.zlayer = zlayer
+layer.close_reason = close_reason
+layer.hide_reason = hide_reason
 
 return layer
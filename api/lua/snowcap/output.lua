@@ -0,0 +1,57 @@
+-- This Source Code Form is subject to the terms of the Mozilla Public
+-- License, v. 2.0. If a copy of the MPL was not distributed with this
+-- file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+local log = require("snowcap.log")
+local client = require("snowcap.grpc.client").client
+local output_service = require("snowcap.grpc.defs").snowcap.output.v0alpha1.OutputService
+
+---@class snowcap.Output
+local output = {}
+
+---Get information about all currently connected outputs.
+---
+---@return snowcap.output.v0alpha1.OutputInfo[]
+function output.outputs()
+    local response, err = client:unary_request(output_service.ListOutputs, {})
+
+    if err then
+        log:error(err)
+        return {}
+    end
+
+    ---@cast response snowcap.output.v0alpha1.ListOutputsResponse
+
+    return response.outputs or {}
+end
+
+---Callbacks for output events. Each is passed the output's info;
+---for `removed`, this is the output's last known state.
+---
+---@class snowcap.OutputEventHandlers
+---@field added fun(info: snowcap.output.v0alpha1.OutputInfo)?
+---@field changed fun(info: snowcap.output.v0alpha1.OutputInfo)?
+---@field removed fun(info: snowcap.output.v0alpha1.OutputInfo)?
+
+---Do something when an output is connected, changed, or disconnected.
+---
+---@param handlers snowcap.OutputEventHandlers
+function output.on_output_event(handlers)
+    local err = client:server_streaming_request(output_service.WatchOutputs, {}, function(response)
+        ---@cast response snowcap.output.v0alpha1.WatchOutputsResponse
+
+        if response.added and handlers.added then
+            handlers.added(response.added)
+        elseif response.changed and handlers.changed then
+            handlers.changed(response.changed)
+        elseif response.removed and handlers.removed then
+            handlers.removed(response.removed)
+        end
+    end)
+
+    if err then
+        log:error(err)
+    end
+end
+
+return output
@@ -0,0 +1,207 @@
+-- This Source Code Form is subject to the terms of the Mozilla Public
+-- License, v. 2.0. If a copy of the MPL was not distributed with this
+-- file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+local log = require("snowcap.log")
+local client = require("snowcap.grpc.client").client
+local popup_service = require("snowcap.grpc.defs").snowcap.popup.v0alpha1.PopupService
+
+local widget = require("snowcap.widget")
+
+---Popups attached to layers, for menus and tooltips.
+---
+---Popups are created with `LayerHandle:new_popup` or `PopupHandle:new_popup`.
+---
+---@class Popup
+local popup = {}
+
+---@class PopupHandleModule
+local popup_handle = {}
+
+---@class PopupHandle
+---@field id integer
+local PopupHandle = {}
+
+function popup_handle.new(id)
+    ---@type PopupHandle
+    local self = {
+        id = id,
+    }
+    setmetatable(self, { __index = PopupHandle })
+    return self
+end
+
+---A point on a popup's anchor rectangle, or the direction a popup extends in.
+---@enum snowcap.popup.Edge
+local edge = {
+    NONE = 1,
+    TOP = 2,
+    BOTTOM = 3,
+    LEFT = 4,
+    RIGHT = 5,
+    TOP_LEFT = 6,
+    BOTTOM_LEFT = 7,
+    TOP_RIGHT = 8,
+    BOTTOM_RIGHT = 9,
+}
+
+---How the compositor may adjust a popup that would end up off-screen.
+---
+---These are bitflags and can be combined with `|` (Lua 5.3+).
+---
+---@enum snowcap.popup.ConstraintAdjustment
+local constraint_adjustment = {
+    SLIDE_X = 1,
+    SLIDE_Y = 2,
+    FLIP_X = 4,
+    FLIP_Y = 8,
+    RESIZE_X = 16,
+    RESIZE_Y = 32,
+}
+
+---Why a popup was closed.
+---@enum snowcap.PopupCloseReason
+local close_reason = {
+    UNKNOWN = 0,
+    ---The compositor dismissed the popup, for example because of a click outside of it
+    DISMISSED = 1,
+    ---A client or the popup's widgets asked for the popup to be closed
+    REQUEST = 2,
+    ---The layer or popup this popup is attached to was closed
+    PARENT_CLOSED = 3,
+}
+
+---The area of the parent a popup is positioned relative to, in logical coordinates.
+---@class snowcap.popup.Rect
+---@field x integer
+---@field y integer
+---@field width integer
+---@field height integer
+
+---@class PopupArgs
+---@field widget snowcap.WidgetDef
+---@field width integer
+---@field height integer
+---@field anchor_rect snowcap.popup.Rect? The area of the parent the popup is positioned relative to. Defaults to the parent's top-left pixel.
+---@field anchor snowcap.popup.Edge? The point on the anchor rect the popup is placed at
+---@field gravity snowcap.popup.Edge? The direction the popup extends in from the anchor point
+---@field constraint_adjustment snowcap.popup.ConstraintAdjustment|integer? How the compositor may move or resize the popup to keep it on-screen
+---@field offset { x: integer, y: integer }? An offset from the anchor point
+---@field grab boolean? Take keyboard focus and get dismissed when clicking outside of the popup. This only works right after a pointer button or key press on the parent.
+
+---@nodoc
+---@param parent_id integer
+---@param args PopupArgs
+---@return PopupHandle|nil handle A handle to the popup, or nil if an error occurred.
+function popup.new_popup(parent_id, args)
+    ---@type snowcap.popup.v0alpha1.NewPopupRequest
+    local request = {
+        parent_id = parent_id,
+        widget_def = widget.widget_def_into_api(args.widget),
+        width = args.width,
+        height = args.height,
+        anchor_rect = args.anchor_rect,
+        anchor = args.anchor,
+        gravity = args.gravity,
+        constraint_adjustment = args.constraint_adjustment,
+        offset_x = args.offset and args.offset.x,
+        offset_y = args.offset and args.offset.y,
+        grab = args.grab,
+    }
+
+    local response, err = client:unary_request(popup_service.NewPopup, request)
+
+    if err then
+        log:error(err)
+        return nil
+    end
+
+    ---@cast response snowcap.popup.v0alpha1.NewPopupResponse
+
+    if not response.popup_id then
+        log:error("no popup_id received")
+        return nil
+    end
+
+    return popup_handle.new(response.popup_id)
+end
+
+---Create a popup attached to this popup, like a submenu.
+---
+---@param args PopupArgs
+---@return PopupHandle|nil handle A handle to the popup, or nil if an error occurred.
+function PopupHandle:new_popup(args)
+    return popup.new_popup(self.id, args)
+end
+
+---Replace this popup's widget tree with a new one.
+---
+---@param widget_def snowcap.WidgetDef
+function PopupHandle:update(widget_def)
+    local _, err = client:unary_request(popup_service.UpdatePopup, {
+        popup_id = self.id,
+        widget_def = widget.widget_def_into_api(widget_def),
+    })
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Callbacks for popup events.
+---
+---@class snowcap.PopupEventHandlers
+---@field configured fun(x: integer, y: integer, width: integer, height: integer)? The compositor placed the popup, relative to its parent
+---@field closed fun(reason: snowcap.PopupCloseReason)? The popup was closed
+
+---Do something whenever anything happens to this popup.
+---
+---@param handlers snowcap.PopupEventHandlers
+function PopupHandle:on_event(handlers)
+    local err = client:server_streaming_request(
+        popup_service.WatchPopup,
+        { popup_id = self.id },
+        function(response)
+            ---@cast response snowcap.popup.v0alpha1.WatchPopupResponse
+
+            if response.configured and handlers.configured then
+                handlers.configured(
+                    response.configured.x or 0,
+                    response.configured.y or 0,
+                    response.configured.width or 0,
+                    response.configured.height or 0
+                )
+            elseif response.closed and handlers.closed then
+                handlers.closed(response.closed.reason or close_reason.UNKNOWN)
+            end
+        end
+    )
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Do something when this popup is closed.
+---
+---After this, the handle no longer refers to a live popup.
+---
+---@param on_close fun(reason: snowcap.PopupCloseReason)
+function PopupHandle:on_close(on_close)
+    self:on_event({ closed = on_close })
+end
+
+---Close this popup and any popups attached to it.
+function PopupHandle:close()
+    local _, err = client:unary_request(popup_service.Close, { popup_id = self.id })
+
+    if err then
+        log:error(err)
+    end
+end
+
+popup.edge = edge
+popup.constraint_adjustment = constraint_adjustment
+popup.close_reason = close_reason
+
+return popup
@@ -2,12 +2,18 @@
 -- License, v. 2.0. If a copy of the MPL was not distributed with this
 -- file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+local log = require("snowcap.log")
+local client = require("snowcap.grpc.client").client
+local widget_service = require("snowcap.grpc.defs").snowcap.widget.v0alpha1.WidgetService
+
 ---@class snowcap.WidgetDef
 ---@field text snowcap.Text?
 ---@field column snowcap.Column?
 ---@field row snowcap.Row?
 ---@field scrollable snowcap.Scrollable?
 ---@field container snowcap.Container?
+---@field button snowcap.Button?
+---@field text_input snowcap.TextInput?
 
 ---@class snowcap.Text
 ---@field text string
@@ -38,6 +44,38 @@
 ---@field clip boolean?
 ---@field children snowcap.WidgetDef[]
 
+---A clickable button. Buttons without an `id` are disabled.
+---@class snowcap.Button
+---@field child snowcap.WidgetDef
+---@field padding snowcap.Padding?
+---@field width snowcap.Length?
+---@field height snowcap.Length?
+---@field style snowcap.ButtonStyle?
+---@field hovered_style snowcap.ButtonStyle? Overrides `style` while the pointer is over the button
+---@field pressed_style snowcap.ButtonStyle? Overrides `style` and `hovered_style` while the button is being pressed
+---@field id integer? The id this button is told apart by in `widget.on_press`
+
+---@class snowcap.ButtonStyle
+---@field text_color snowcap.Color?
+---@field background_color snowcap.Color?
+---@field border_radius number?
+---@field border_thickness number?
+---@field border_color snowcap.Color?
+
+---A single-line text field.
+---
+---Snowcap keeps track of what is typed into it. When the widget tree is updated,
+---the typed text is kept unless `value` changed.
+---@class snowcap.TextInput
+---@field placeholder string? Text shown while the input is empty
+---@field value string? The text the input starts with
+---@field font snowcap.Font?
+---@field size number?
+---@field padding snowcap.Padding?
+---@field width snowcap.Length?
+---@field password boolean? Hide what is typed
+---@field id integer? The id this input is told apart by in `widget.on_text_changed` and `widget.on_text_submitted`
+
 ---@class snowcap.Scrollable
 ---@field width snowcap.Length?
 ---@field height snowcap.Length?
@@ -69,6 +107,7 @@
 ---@field border_radius number?
 ---@field border_thickness number?
 ---@field border_color snowcap.Color?
+---@field interactive boolean? Whether this container accepts pointer input when the layer's input region is automatic
 
 local scrollable = {
     ---@enum snowcap.Scrollable.Alignment
@@ -244,6 +283,7 @@ local function container_into_api(def)
         border_radius = def.border_radius,
         border_thickness = def.border_thickness,
         border_color = def.border_color --[[@as snowcap.widget.v0alpha1.Color]],
+        interactive = def.interactive,
     }
 end
 
@@ -300,6 +340,38 @@ local function scrollable_into_api(def)
     }
 end
 
+---@param def snowcap.Button
+---@return snowcap.widget.v0alpha1.Button
+local function button_into_api(def)
+    ---@type snowcap.widget.v0alpha1.Button
+    return {
+        child = widget.widget_def_into_api(def.child),
+        padding = def.padding --[[@as snowcap.widget.v0alpha1.Padding]],
+        width = def.width --[[@as snowcap.widget.v0alpha1.Length]],
+        height = def.height --[[@as snowcap.widget.v0alpha1.Length]],
+        style = def.style --[[@as snowcap.widget.v0alpha1.ButtonStyle]],
+        hovered_style = def.hovered_style --[[@as snowcap.widget.v0alpha1.ButtonStyle]],
+        pressed_style = def.pressed_style --[[@as snowcap.widget.v0alpha1.ButtonStyle]],
+        widget_id = def.id,
+    }
+end
+
+---@param def snowcap.TextInput
+---@return snowcap.widget.v0alpha1.TextInput
+local function text_input_into_api(def)
+    ---@type snowcap.widget.v0alpha1.TextInput
+    return {
+        placeholder = def.placeholder,
+        value = def.value,
+        font = def.font --[[@as snowcap.widget.v0alpha1.Font]],
+        pixels = def.size,
+        padding = def.padding --[[@as snowcap.widget.v0alpha1.Padding]],
+        width = def.width --[[@as snowcap.widget.v0alpha1.Length]],
+        password = def.password,
+        widget_id = def.id,
+    }
+end
+
 ---@param def snowcap.WidgetDef
 ---@return snowcap.widget.v0alpha1.WidgetDef
 function widget.widget_def_into_api(def)
@@ -318,6 +390,12 @@ function widget.widget_def_into_api(def)
     if def.scrollable then
         def.scrollable = scrollable_into_api(def.scrollable)
     end
+    if def.button then
+        def.button = button_into_api(def.button)
+    end
+    if def.text_input then
+        def.text_input = text_input_into_api(def.text_input)
+    end
 
     return def --[[@as snowcap.widget.v0alpha1.WidgetDef]]
 end
@@ -367,4 +445,89 @@ function widget.container(container)
     }
 end
 
+---@param button snowcap.Button
+---
+---@return snowcap.WidgetDef
+function widget.button(button)
+    return {
+        button = button,
+    }
+end
+
+---@param text_input snowcap.TextInput
+---
+---@return snowcap.WidgetDef
+function widget.text_input(text_input)
+    return {
+        text_input = text_input,
+    }
+end
+
+---@param parent LayerHandle|PopupHandle
+---@param widget_id integer
+---@param on_event fun(response: snowcap.widget.v0alpha1.WatchWidgetEventsResponse)
+local function watch_widget_events(parent, widget_id, on_event)
+    local err = client:server_streaming_request(
+        widget_service.WatchWidgetEvents,
+        { id = parent.id },
+        function(response)
+            ---@cast response snowcap.widget.v0alpha1.WatchWidgetEventsResponse
+
+            if response.widget_id == widget_id then
+                on_event(response)
+            end
+        end
+    )
+
+    if err then
+        log:error(err)
+    end
+end
+
+---Do something when the button with the given id is pressed.
+---
+---`parent` is the layer, layer group, or popup the button is in.
+---For layer groups, presses from every instance are received.
+---
+---@param parent LayerHandle|PopupHandle
+---@param button_id integer
+---@param on_press fun()
+function widget.on_press(parent, button_id, on_press)
+    watch_widget_events(parent, button_id, function(response)
+        if response.button_pressed then
+            on_press()
+        end
+    end)
+end
+
+---Do something with the new text whenever the text in the text input with the given id changes.
+---
+---See `widget.on_press` for what `parent` is.
+---
+---@param parent LayerHandle|PopupHandle
+---@param input_id integer
+---@param on_text_changed fun(text: string)
+function widget.on_text_changed(parent, input_id, on_text_changed)
+    watch_widget_events(parent, input_id, function(response)
+        if response.text_changed then
+            on_text_changed(response.text_changed.text or "")
+        end
+    end)
+end
+
+---Do something with the text when Enter is pressed in the text input with the given id.
+---
+---See `widget.on_press` for what `parent` is.
+---
+---@param parent LayerHandle|PopupHandle
+---@param input_id integer
+---@param on_text_submitted fun(text: string)
+function widget.on_text_submitted(parent, input_id, on_text_submitted)
+    watch_widget_events(parent, input_id, function(response)
+        if response.text_submitted then
+            on_text_submitted(response.text_submitted.text or "")
+        end
+    end)
+end
+
 return widget
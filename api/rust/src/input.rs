@@ -1,6 +1,7 @@
 //! Input types.
 
-use snowcap_api_defs::snowcap::input;
+use snowcap_api_defs::snowcap::input::{self, v0alpha1::KeyboardKeyResponse};
+use xkbcommon::xkb::Keysym;
 
 /// Keyboard modifiers.
 #[allow(missing_docs)]
@@ -10,6 +11,10 @@ pub struct Modifiers {
     pub ctrl: bool,
     pub alt: bool,
     pub logo: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    /// The index of the active keyboard layout.
+    pub layout_index: u32,
 }
 
 impl From<input::v0alpha1::Modifiers> for Modifiers {
@@ -19,6 +24,41 @@ impl From<input::v0alpha1::Modifiers> for Modifiers {
             ctrl: value.ctrl(),
             alt: value.alt(),
             logo: value.super_(),
+            caps_lock: value.caps_lock(),
+            num_lock: value.num_lock(),
+            layout_index: value.layout_index(),
+        }
+    }
+}
+
+/// A key event on a layer.
+pub struct KeyEvent {
+    /// The key's keysym.
+    pub key: Keysym,
+    /// The modifiers held during the event.
+    pub modifiers: Modifiers,
+    /// Whether the key was pressed or released.
+    pub pressed: bool,
+    /// Whether this press is a repeat from the key being held down.
+    pub repeat: bool,
+    /// The text this key press produces, with compose sequences applied.
+    ///
+    /// This is `None` for releases and if the key produces no text or is part of
+    /// an unfinished compose sequence. It may be a control character, like `"\r"` for Return.
+    pub text: Option<String>,
+    /// The name of the keysym, like `"Return"` or `"a"`.
+    pub name: String,
+}
+
+impl From<KeyboardKeyResponse> for KeyEvent {
+    fn from(value: KeyboardKeyResponse) -> Self {
+        Self {
+            key: Keysym::new(value.key()),
+            pressed: value.pressed(),
+            repeat: value.repeat(),
+            name: value.keysym_name().to_string(),
+            modifiers: value.modifiers.unwrap_or_default().into(),
+            text: value.text,
         }
     }
 }
@@ -1,13 +1,18 @@
 //! Support for layer surface widgets using `wlr-layer-shell`.
 
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, time::Duration};
 
 use snowcap_api_defs::snowcap::{
     input::v0alpha1::KeyboardKeyRequest,
     layer::{
         self,
-        v0alpha1::{CloseRequest, NewLayerRequest},
+        v0alpha1::{
+            watch_layer_response, CloseRequest, GetLayerRequest, HideRequest, ListLayersRequest,
+            NewLayerRequest, SetLayerPropertiesRequest, ShowRequest, UpdateLayerRequest,
+            WatchLayerRequest,
+        },
     },
+    widget,
 };
 use tokio_stream::StreamExt;
 use tracing::error;
@@ -15,7 +20,9 @@ use xkbcommon::xkb::Keysym;
 
 use crate::{
     block_on_tokio,
-    input::Modifiers,
+    input::{KeyEvent, Modifiers},
+    output::{OutputInfo, Transform},
+    popup::{NewPopupError, PopupHandle, Position},
     widget::{WidgetDef, WidgetId},
 };
 
@@ -23,33 +30,23 @@ use crate::{
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub struct Layer;
 
-// TODO: change to bitflag
-/// An anchor for a layer surface.
-#[allow(missing_docs)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum Anchor {
-    Top,
-    Bottom,
-    Left,
-    Right,
-    TopLeft,
-    TopRight,
-    BottomLeft,
-    BottomRight,
-}
-
-impl From<Anchor> for layer::v0alpha1::Anchor {
-    fn from(value: Anchor) -> Self {
-        match value {
-            Anchor::Top => layer::v0alpha1::Anchor::Top,
-            Anchor::Bottom => layer::v0alpha1::Anchor::Bottom,
-            Anchor::Left => layer::v0alpha1::Anchor::Left,
-            Anchor::Right => layer::v0alpha1::Anchor::Right,
-            Anchor::TopLeft => layer::v0alpha1::Anchor::TopLeft,
-            Anchor::TopRight => layer::v0alpha1::Anchor::TopRight,
-            Anchor::BottomLeft => layer::v0alpha1::Anchor::BottomLeft,
-            Anchor::BottomRight => layer::v0alpha1::Anchor::BottomRight,
-        }
+bitflags::bitflags! {
+    /// The edges a layer surface is anchored to.
+    ///
+    /// Anchors can be combined. Anchoring to two opposite edges with a size of 0
+    /// on that axis will let the compositor stretch the layer across it,
+    /// for example `Anchor::TOP | Anchor::LEFT | Anchor::RIGHT` with a width of 0
+    /// for a full-width top bar.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Anchor: u32 {
+        /// The top edge.
+        const TOP = 1;
+        /// The bottom edge.
+        const BOTTOM = 2;
+        /// The left edge.
+        const LEFT = 4;
+        /// The right edge.
+        const RIGHT = 8;
     }
 }
 
@@ -74,6 +71,17 @@ impl From<KeyboardInteractivity> for layer::v0alpha1::KeyboardInteractivity {
     }
 }
 
+impl From<layer::v0alpha1::KeyboardInteractivity> for KeyboardInteractivity {
+    fn from(value: layer::v0alpha1::KeyboardInteractivity) -> Self {
+        match value {
+            layer::v0alpha1::KeyboardInteractivity::Unspecified
+            | layer::v0alpha1::KeyboardInteractivity::None => KeyboardInteractivity::None,
+            layer::v0alpha1::KeyboardInteractivity::OnDemand => KeyboardInteractivity::OnDemand,
+            layer::v0alpha1::KeyboardInteractivity::Exclusive => KeyboardInteractivity::Exclusive,
+        }
+    }
+}
+
 /// Layer surface behavior for exclusive zones.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ExclusiveZone {
@@ -97,6 +105,150 @@ impl From<ExclusiveZone> for i32 {
     }
 }
 
+impl From<i32> for ExclusiveZone {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => ExclusiveZone::Respect,
+            x if x < 0 => ExclusiveZone::Ignore,
+            x => ExclusiveZone::Exclusive(NonZeroU32::new(x as u32).expect("x is positive")),
+        }
+    }
+}
+
+/// The distance a layer surface keeps from the edges it is anchored to.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Margin {
+    /// The margin from the top edge.
+    pub top: i32,
+    /// The margin from the right edge.
+    pub right: i32,
+    /// The margin from the bottom edge.
+    pub bottom: i32,
+    /// The margin from the left edge.
+    pub left: i32,
+}
+
+impl From<Margin> for layer::v0alpha1::Margin {
+    fn from(value: Margin) -> Self {
+        Self {
+            top: Some(value.top),
+            right: Some(value.right),
+            bottom: Some(value.bottom),
+            left: Some(value.left),
+        }
+    }
+}
+
+impl From<layer::v0alpha1::Margin> for Margin {
+    fn from(value: layer::v0alpha1::Margin) -> Self {
+        Self {
+            top: value.top(),
+            right: value.right(),
+            bottom: value.bottom(),
+            left: value.left(),
+        }
+    }
+}
+
+/// How long a layer stays open before closing or hiding itself.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Timeout {
+    /// The time until the layer closes.
+    pub duration: Duration,
+    /// Whether the countdown is paused while the pointer is over the layer.
+    pub pause_on_hover: bool,
+    /// Whether to hide the layer instead of closing it.
+    ///
+    /// The countdown starts over when the layer is shown again.
+    pub hide: bool,
+}
+
+/// The part of a layer that accepts pointer input.
+///
+/// Pointer input outside of it goes to whatever is beneath the layer,
+/// which lets transparent parts of a layer be clicked through.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum InputRegion {
+    /// The whole layer accepts input.
+    #[default]
+    Full,
+    /// Only these rectangles, as `(x, y, width, height)` in logical coordinates, accept input.
+    Rects(Vec<(i32, i32, u32, u32)>),
+    /// Only the bounds of [`Container`][crate::widget::Container]s marked interactive
+    /// accept input. This is recomputed whenever the layer's widgets are laid out.
+    Automatic,
+}
+
+impl From<InputRegion> for layer::v0alpha1::InputRegion {
+    fn from(value: InputRegion) -> Self {
+        let region = match value {
+            InputRegion::Full => None,
+            InputRegion::Rects(rects) => Some(layer::v0alpha1::input_region::Region::Rects(
+                layer::v0alpha1::input_region::Rects {
+                    rects: rects
+                        .into_iter()
+                        .map(
+                            |(x, y, width, height)| layer::v0alpha1::input_region::Rect {
+                                x: Some(x),
+                                y: Some(y),
+                                width: Some(width),
+                                height: Some(height),
+                            },
+                        )
+                        .collect(),
+                },
+            )),
+            InputRegion::Automatic => Some(layer::v0alpha1::input_region::Region::Automatic(())),
+        };
+
+        Self { region }
+    }
+}
+
+/// Which output a layer surface should be placed on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OutputSelector {
+    /// The output with the given name, like "DP-1".
+    Name(String),
+    /// The output with the given description.
+    Description(String),
+    /// One instance of the layer on every output matching the filter,
+    /// including outputs connected later.
+    ///
+    /// The returned [`LayerHandle`] controls every instance at once.
+    PerOutput(OutputFilter),
+}
+
+/// Which outputs a layer created with [`OutputSelector::PerOutput`] is placed on.
+///
+/// The default filter matches every output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OutputFilter {
+    /// Only match outputs with one of these names. If empty, any name matches.
+    pub names: Vec<String>,
+    /// Only match outputs whose description contains this string.
+    pub description_contains: Option<String>,
+}
+
+impl From<OutputFilter> for layer::v0alpha1::OutputFilter {
+    fn from(value: OutputFilter) -> Self {
+        Self {
+            names: value.names,
+            description_contains: value.description_contains,
+        }
+    }
+}
+
+impl From<OutputSelector> for layer::v0alpha1::new_layer_request::Output {
+    fn from(value: OutputSelector) -> Self {
+        match value {
+            OutputSelector::Name(name) => Self::OutputName(name),
+            OutputSelector::Description(desc) => Self::OutputDescription(desc),
+            OutputSelector::PerOutput(filter) => Self::PerOutput(filter.into()),
+        }
+    }
+}
+
 /// The layer on which a layer surface will be drawn.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -107,6 +259,17 @@ pub enum ZLayer {
     Overlay,
 }
 
+impl From<layer::v0alpha1::Layer> for ZLayer {
+    fn from(value: layer::v0alpha1::Layer) -> Self {
+        match value {
+            layer::v0alpha1::Layer::Background => Self::Background,
+            layer::v0alpha1::Layer::Bottom => Self::Bottom,
+            layer::v0alpha1::Layer::Unspecified | layer::v0alpha1::Layer::Top => Self::Top,
+            layer::v0alpha1::Layer::Overlay => Self::Overlay,
+        }
+    }
+}
+
 impl From<ZLayer> for layer::v0alpha1::Layer {
     fn from(value: ZLayer) -> Self {
         match value {
@@ -118,7 +281,172 @@ impl From<ZLayer> for layer::v0alpha1::Layer {
     }
 }
 
-/// The error type for [`Layer::new_widget`].
+/// A set of layer surface properties to change with [`LayerHandle::set_properties`].
+///
+/// Properties that are left as `None` will not be changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LayerProperties {
+    /// The new width of the layer, or its maximum width if it's auto-sized.
+    pub width: Option<u32>,
+    /// The new height of the layer, or its maximum height if it's auto-sized.
+    pub height: Option<u32>,
+    /// The new anchor of the layer.
+    pub anchor: Option<Anchor>,
+    /// The new keyboard interactivity of the layer.
+    pub keyboard_interactivity: Option<KeyboardInteractivity>,
+    /// The new exclusive zone behavior of the layer.
+    pub exclusive_zone: Option<ExclusiveZone>,
+    /// The new z-layer of the layer.
+    pub layer: Option<ZLayer>,
+    /// The new input region of the layer.
+    pub input_region: Option<InputRegion>,
+}
+
+impl LayerProperties {
+    /// Create a new, empty set of properties.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the new size, or the maximum size if the layer is auto-sized.
+    pub fn size(self, width: u32, height: u32) -> Self {
+        Self {
+            width: Some(width),
+            height: Some(height),
+            ..self
+        }
+    }
+
+    /// Set the new anchor.
+    pub fn anchor(self, anchor: Anchor) -> Self {
+        Self {
+            anchor: Some(anchor),
+            ..self
+        }
+    }
+
+    /// Set the new keyboard interactivity.
+    pub fn keyboard_interactivity(self, keyboard_interactivity: KeyboardInteractivity) -> Self {
+        Self {
+            keyboard_interactivity: Some(keyboard_interactivity),
+            ..self
+        }
+    }
+
+    /// Set the new exclusive zone behavior.
+    pub fn exclusive_zone(self, exclusive_zone: ExclusiveZone) -> Self {
+        Self {
+            exclusive_zone: Some(exclusive_zone),
+            ..self
+        }
+    }
+
+    /// Set the new z-layer.
+    pub fn layer(self, layer: ZLayer) -> Self {
+        Self {
+            layer: Some(layer),
+            ..self
+        }
+    }
+
+    /// Set the new input region.
+    pub fn input_region(self, input_region: InputRegion) -> Self {
+        Self {
+            input_region: Some(input_region),
+            ..self
+        }
+    }
+}
+
+/// Optional settings for [`Layer::new_widget_with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NewLayerOptions {
+    /// The distance the layer keeps from the edges it is anchored to.
+    pub margin: Option<Margin>,
+    /// The output the layer is placed on.
+    ///
+    /// If `None`, the compositor decides.
+    pub output: Option<OutputSelector>,
+    /// Whether the layer stays open after this client disconnects from Snowcap.
+    pub keep_on_disconnect: bool,
+    /// The namespace the compositor uses to tell layers apart to apply rules to them.
+    ///
+    /// If `None`, it defaults to "snowcap".
+    pub namespace: Option<String>,
+    /// Whether the layer is sized to fit its widgets, re-measuring whenever they change.
+    ///
+    /// The layer's width and height are then the maximum size.
+    pub auto_size: bool,
+    /// How long the layer stays open before closing itself with [`CloseReason::TimedOut`],
+    /// or hiding itself if [`Timeout::hide`] is true.
+    pub timeout: Option<Timeout>,
+    /// The part of the layer that accepts pointer input.
+    ///
+    /// If `None`, the whole layer does.
+    pub input_region: Option<InputRegion>,
+}
+
+impl NewLayerOptions {
+    /// Create a new set of options with everything left at its default.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the margin.
+    pub fn margin(self, margin: Margin) -> Self {
+        Self {
+            margin: Some(margin),
+            ..self
+        }
+    }
+
+    /// Set the output.
+    pub fn output(self, output: OutputSelector) -> Self {
+        Self {
+            output: Some(output),
+            ..self
+        }
+    }
+
+    /// Set whether the layer stays open after this client disconnects.
+    pub fn keep_on_disconnect(self, keep_on_disconnect: bool) -> Self {
+        Self {
+            keep_on_disconnect,
+            ..self
+        }
+    }
+
+    /// Set the namespace.
+    pub fn namespace(self, namespace: impl ToString) -> Self {
+        Self {
+            namespace: Some(namespace.to_string()),
+            ..self
+        }
+    }
+
+    /// Set whether the layer is sized to fit its widgets.
+    pub fn auto_size(self, auto_size: bool) -> Self {
+        Self { auto_size, ..self }
+    }
+
+    /// Set the timeout.
+    pub fn timeout(self, timeout: Timeout) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the input region.
+    pub fn input_region(self, input_region: InputRegion) -> Self {
+        Self {
+            input_region: Some(input_region),
+            ..self
+        }
+    }
+}
+
+/// The error type for [`Layer::new_widget`] and [`Layer::new_widget_with_options`].
 #[derive(thiserror::Error, Debug)]
 pub enum NewLayerError {
     /// Snowcap returned a gRPC error status.
@@ -130,7 +458,26 @@ pub enum NewLayerError {
 }
 
 impl Layer {
+    /// Get information about all live layers, including ones created by other clients.
+    pub fn layers(&self) -> Vec<LayerInfo> {
+        match block_on_tokio(crate::layer().list_layers(ListLayersRequest {})) {
+            Ok(response) => response
+                .into_inner()
+                .layers
+                .into_iter()
+                .map(LayerInfo::from)
+                .collect(),
+            Err(status) => {
+                error!("Failed to list layers: {status}");
+                Vec::new()
+            }
+        }
+    }
+
     /// Create a new widget.
+    ///
+    /// The layer is closed when this client disconnects from Snowcap.
+    /// See [`Layer::new_widget_with_options`] for more settings.
     pub fn new_widget(
         &self,
         widget: impl Into<WidgetDef>,
@@ -141,16 +488,66 @@ impl Layer {
         exclusive_zone: ExclusiveZone,
         layer: ZLayer,
     ) -> Result<LayerHandle, NewLayerError> {
+        self.new_widget_with_options(
+            widget,
+            width,
+            height,
+            anchor,
+            keyboard_interactivity,
+            exclusive_zone,
+            layer,
+            NewLayerOptions::default(),
+        )
+    }
+
+    /// Create a new widget with the given [`NewLayerOptions`].
+    ///
+    /// If the layer is auto-sized, `width` and `height` are its maximum size.
+    pub fn new_widget_with_options(
+        &self,
+        widget: impl Into<WidgetDef>,
+        width: u32,
+        height: u32,
+        anchor: Option<Anchor>,
+        keyboard_interactivity: KeyboardInteractivity,
+        exclusive_zone: ExclusiveZone,
+        layer: ZLayer,
+        options: NewLayerOptions,
+    ) -> Result<LayerHandle, NewLayerError> {
+        let NewLayerOptions {
+            margin,
+            output,
+            keep_on_disconnect,
+            namespace,
+            auto_size,
+            timeout,
+            input_region,
+        } = options;
+
+        // Durations too long to fit are as good as never timing out
+        let timeout_ms =
+            timeout.map(|timeout| u32::try_from(timeout.duration.as_millis()).unwrap_or(u32::MAX));
+
         let response = block_on_tokio(crate::layer().new_layer(NewLayerRequest {
             widget_def: Some(widget.into().into()),
             width: Some(width),
             height: Some(height),
-            anchor: anchor.map(|anchor| layer::v0alpha1::Anchor::from(anchor) as i32),
+            anchor: None,
             keyboard_interactivity: Some(layer::v0alpha1::KeyboardInteractivity::from(
                 keyboard_interactivity,
             ) as i32),
             exclusive_zone: Some(exclusive_zone.into()),
             layer: Some(layer::v0alpha1::Layer::from(layer) as i32),
+            margin: margin.map(From::from),
+            anchor_flags: anchor.map(|anchor| anchor.bits()),
+            output: output.map(From::from),
+            keep_on_disconnect: Some(keep_on_disconnect),
+            namespace,
+            auto_size: Some(auto_size),
+            timeout_ms,
+            pause_timeout_on_hover: timeout.map(|timeout| timeout.pause_on_hover),
+            hide_on_timeout: timeout.map(|timeout| timeout.hide),
+            input_region: input_region.map(From::from),
         }))?;
 
         let id = response
@@ -162,13 +559,233 @@ impl Layer {
     }
 }
 
+/// A description of a live layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerInfo {
+    /// A handle to the layer.
+    pub handle: LayerHandle,
+    /// The layer's namespace.
+    pub namespace: String,
+    /// The layer's current width.
+    pub width: u32,
+    /// The layer's current height.
+    pub height: u32,
+    /// The layer's current scale, which may be fractional.
+    pub scale: f64,
+    /// The edges the layer is anchored to.
+    pub anchor: Anchor,
+    /// The layer the layer surface is drawn on.
+    pub layer: ZLayer,
+    /// The layer's exclusive zone.
+    pub exclusive_zone: ExclusiveZone,
+    /// The layer's keyboard interactivity.
+    pub keyboard_interactivity: KeyboardInteractivity,
+    /// The layer's margin.
+    pub margin: Margin,
+    /// The raw definition of the widgets the layer is showing.
+    pub widget_def: Option<widget::v0alpha1::WidgetDef>,
+    /// A handle to the layer group this layer is an instance of, if any.
+    pub group: Option<LayerHandle>,
+    /// An id for the connection of the client that created the layer.
+    pub owner_client_id: Option<u32>,
+    /// The process id of the client that created the layer, if known.
+    pub owner_pid: Option<i32>,
+    /// Whether the layer outlives the connection of the client that created it.
+    pub keep_on_disconnect: bool,
+    /// Whether the layer is sized to fit its widgets.
+    pub auto_size: bool,
+    /// Whether the layer is hidden.
+    pub hidden: bool,
+    /// The transform of the output the layer is on.
+    pub transform: Transform,
+    /// The outputs the layer is currently on.
+    ///
+    /// This tells which output a layer ended up on when the compositor chose it.
+    pub outputs: Vec<OutputInfo>,
+}
+
+impl From<layer::v0alpha1::LayerInfo> for LayerInfo {
+    fn from(value: layer::v0alpha1::LayerInfo) -> Self {
+        Self {
+            handle: LayerHandle {
+                id: value.layer_id().into(),
+            },
+            keep_on_disconnect: value.keep_on_disconnect(),
+            auto_size: value.auto_size(),
+            hidden: value.hidden(),
+            transform: value.transform().into(),
+            namespace: value.namespace().to_string(),
+            width: value.width(),
+            height: value.height(),
+            scale: value
+                .fractional_scale
+                .unwrap_or_else(|| value.scale() as f64),
+            anchor: Anchor::from_bits_truncate(value.anchor_flags()),
+            layer: value.layer().into(),
+            exclusive_zone: value.exclusive_zone().into(),
+            keyboard_interactivity: value.keyboard_interactivity().into(),
+            margin: value.margin.map(Margin::from).unwrap_or_default(),
+            group: value.group_id.map(|id| LayerHandle { id: id.into() }),
+            owner_client_id: value.owner_client_id,
+            owner_pid: value.owner_pid,
+            widget_def: value.widget_def,
+            outputs: value.outputs.into_iter().map(OutputInfo::from).collect(),
+        }
+    }
+}
+
+/// Why a layer was closed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CloseReason {
+    /// The compositor closed the layer.
+    Compositor,
+    /// A client or the layer's widgets asked for the layer to be closed.
+    Request,
+    /// The output a layer group instance was on was disconnected
+    /// or no longer matches the group's filter.
+    OutputRemoved,
+    /// The client that created the layer disconnected.
+    ClientDisconnected,
+    /// The layer's [`Timeout`] ran out.
+    TimedOut,
+    /// Snowcap didn't say why.
+    Unknown,
+}
+
+impl From<layer::v0alpha1::CloseReason> for CloseReason {
+    fn from(value: layer::v0alpha1::CloseReason) -> Self {
+        match value {
+            layer::v0alpha1::CloseReason::Unspecified => Self::Unknown,
+            layer::v0alpha1::CloseReason::Compositor => Self::Compositor,
+            layer::v0alpha1::CloseReason::Request => Self::Request,
+            layer::v0alpha1::CloseReason::OutputRemoved => Self::OutputRemoved,
+            layer::v0alpha1::CloseReason::ClientDisconnected => Self::ClientDisconnected,
+            layer::v0alpha1::CloseReason::TimedOut => Self::TimedOut,
+        }
+    }
+}
+
+/// Why a layer was hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HideReason {
+    /// A client asked for the layer to be hidden.
+    Request,
+    /// The layer's [`Timeout`] ran out.
+    TimedOut,
+    /// The reason is unknown.
+    Unknown,
+}
+
+impl From<layer::v0alpha1::HideReason> for HideReason {
+    fn from(value: layer::v0alpha1::HideReason) -> Self {
+        match value {
+            layer::v0alpha1::HideReason::Unspecified => Self::Unknown,
+            layer::v0alpha1::HideReason::Request => Self::Request,
+            layer::v0alpha1::HideReason::TimedOut => Self::TimedOut,
+        }
+    }
+}
+
+/// Something that happened to a layer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerEvent {
+    /// The compositor configured the layer with a new size.
+    Configured {
+        /// The new width.
+        width: u32,
+        /// The new height.
+        height: u32,
+    },
+    /// The layer was closed.
+    Closed(CloseReason),
+    /// The layer's scale changed. It may be fractional.
+    ScaleChanged(f64),
+    /// The layer entered an output.
+    OutputEntered {
+        /// The output's id, as in [`OutputInfo`].
+        id: u32,
+        /// The output's name.
+        name: Option<String>,
+    },
+    /// The layer left an output.
+    OutputLeft {
+        /// The output's id, as in [`OutputInfo`].
+        id: u32,
+        /// The output's name.
+        name: Option<String>,
+    },
+    /// The layer gained keyboard focus.
+    FocusGained,
+    /// The layer lost keyboard focus.
+    FocusLost,
+    /// The layer was hidden.
+    Hidden(HideReason),
+    /// The layer was shown again after being hidden.
+    Shown,
+    /// The transform of the output the layer is on changed.
+    TransformChanged(Transform),
+}
+
+impl From<watch_layer_response::Event> for LayerEvent {
+    fn from(value: watch_layer_response::Event) -> Self {
+        match value {
+            watch_layer_response::Event::Configured(configured) => Self::Configured {
+                width: configured.width(),
+                height: configured.height(),
+            },
+            watch_layer_response::Event::Closed(closed) => Self::Closed(closed.reason().into()),
+            watch_layer_response::Event::ScaleChanged(scale_changed) => Self::ScaleChanged(
+                scale_changed
+                    .fractional_scale
+                    .unwrap_or_else(|| scale_changed.scale() as f64),
+            ),
+            watch_layer_response::Event::OutputEntered(entered) => Self::OutputEntered {
+                id: entered.output_id(),
+                name: entered.output_name,
+            },
+            watch_layer_response::Event::OutputLeft(left) => Self::OutputLeft {
+                id: left.output_id(),
+                name: left.output_name,
+            },
+            watch_layer_response::Event::FocusGained(_) => Self::FocusGained,
+            watch_layer_response::Event::FocusLost(_) => Self::FocusLost,
+            watch_layer_response::Event::Hidden(hidden) => Self::Hidden(hidden.reason().into()),
+            watch_layer_response::Event::Shown(_) => Self::Shown,
+            watch_layer_response::Event::TransformChanged(changed) => {
+                Self::TransformChanged(changed.transform().into())
+            }
+        }
+    }
+}
+
 /// A handle to a layer surface widget.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct LayerHandle {
     id: WidgetId,
 }
 
+impl From<LayerHandle> for WidgetId {
+    fn from(value: LayerHandle) -> Self {
+        value.id
+    }
+}
+
 impl LayerHandle {
+    /// Get information about this layer.
+    ///
+    /// Returns `None` if the layer no longer exists or this handle refers to a layer group.
+    pub fn info(&self) -> Option<LayerInfo> {
+        match block_on_tokio(crate::layer().get_layer(GetLayerRequest {
+            layer_id: Some(self.id.into_inner()),
+        })) {
+            Ok(response) => response.into_inner().layer.map(LayerInfo::from),
+            Err(status) => {
+                error!("Failed to get info for {self:?}: {status}");
+                None
+            }
+        }
+    }
+
     /// Close this layer widget.
     pub fn close(&self) {
         if let Err(status) = block_on_tokio(crate::layer().close(CloseRequest {
@@ -178,17 +795,195 @@ impl LayerHandle {
         }
     }
 
+    /// Hide this layer.
+    ///
+    /// The layer's widgets keep their state, like scroll positions,
+    /// and are shown as they were when [`LayerHandle::show`] is called.
+    pub fn hide(&self) {
+        if let Err(status) = block_on_tokio(crate::layer().hide(HideRequest {
+            layer_id: Some(self.id.into_inner()),
+        })) {
+            error!("Failed to hide {self:?}: {status}");
+        }
+    }
+
+    /// Show this layer again after it was hidden.
+    pub fn show(&self) {
+        if let Err(status) = block_on_tokio(crate::layer().show(ShowRequest {
+            layer_id: Some(self.id.into_inner()),
+        })) {
+            error!("Failed to show {self:?}: {status}");
+        }
+    }
+
+    /// Create a popup attached to this layer, like a menu or tooltip.
+    ///
+    /// `position` is relative to this layer.
+    ///
+    /// If `grab` is true, the popup takes keyboard focus and is dismissed
+    /// when clicking outside of it. This only works right after a pointer button
+    /// or key press on this layer, for example in response to a click.
+    pub fn new_popup(
+        &self,
+        widget: impl Into<WidgetDef>,
+        width: u32,
+        height: u32,
+        position: Position,
+        grab: bool,
+    ) -> Result<PopupHandle, NewPopupError> {
+        crate::popup::new_popup(self.id, widget.into(), width, height, position, grab)
+    }
+
+    /// Replace this layer's widget tree with a new one.
+    ///
+    /// The layer surface is kept alive, so this can be used to update
+    /// frequently changing content without the layer flickering.
+    pub fn update(&self, widget: impl Into<WidgetDef>) {
+        if let Err(status) = block_on_tokio(crate::layer().update_layer(UpdateLayerRequest {
+            layer_id: Some(self.id.into_inner()),
+            widget_def: Some(widget.into().into()),
+        })) {
+            error!("Failed to update {self:?}: {status}");
+        }
+    }
+
+    /// Change properties of this layer.
+    ///
+    /// Changing the z-layer requires version 2 of `wlr-layer-shell`.
+    pub fn set_properties(&self, properties: LayerProperties) {
+        let LayerProperties {
+            width,
+            height,
+            anchor,
+            keyboard_interactivity,
+            exclusive_zone,
+            layer: z_layer,
+            input_region,
+        } = properties;
+
+        if let Err(status) = block_on_tokio(crate::layer().set_layer_properties(
+            SetLayerPropertiesRequest {
+                layer_id: Some(self.id.into_inner()),
+                width,
+                height,
+                anchor: None,
+                keyboard_interactivity: keyboard_interactivity.map(|interactivity| {
+                    layer::v0alpha1::KeyboardInteractivity::from(interactivity) as i32
+                }),
+                exclusive_zone: exclusive_zone.map(From::from),
+                layer: z_layer.map(|z_layer| layer::v0alpha1::Layer::from(z_layer) as i32),
+                anchor_flags: anchor.map(|anchor| anchor.bits()),
+                input_region: input_region.map(From::from),
+            },
+        )) {
+            error!("Failed to set properties of {self:?}: {status}");
+        }
+    }
+
+    /// Do something when the compositor configures this layer with a new size.
+    ///
+    /// This is useful for layers anchored to opposite edges, whose size
+    /// is chosen by the compositor.
+    pub fn on_configure(
+        &self,
+        mut on_configure: impl FnMut(LayerHandle, u32, u32) + Send + 'static,
+    ) {
+        self.on_event(move |handle, event| {
+            if let LayerEvent::Configured { width, height } = event {
+                on_configure(handle, width, height);
+            }
+        });
+    }
+
+    /// Do something when this layer is closed.
+    ///
+    /// After this, the handle no longer refers to a live layer.
+    pub fn on_close(&self, mut on_close: impl FnMut(LayerHandle, CloseReason) + Send + 'static) {
+        self.on_event(move |handle, event| {
+            if let LayerEvent::Closed(reason) = event {
+                on_close(handle, reason);
+            }
+        });
+    }
+
+    /// Do something whenever anything happens to this layer.
+    ///
+    /// If this handle refers to a layer group, events from every instance are received.
+    pub fn on_event(&self, mut on_event: impl FnMut(LayerHandle, LayerEvent) + Send + 'static) {
+        let mut stream = match block_on_tokio(crate::layer().watch_layer(WatchLayerRequest {
+            layer_id: Some(self.id.into_inner()),
+        })) {
+            Ok(stream) => stream.into_inner(),
+            Err(status) => {
+                error!("Failed to watch layer: {status}");
+                return;
+            }
+        };
+
+        let handle = *self;
+
+        tokio::spawn(async move {
+            while let Some(Ok(response)) = stream.next().await {
+                let Some(event) = response.event else {
+                    continue;
+                };
+
+                on_event(handle, event.into());
+            }
+        });
+    }
+
     /// Do something on key press.
+    ///
+    /// This is not called again while a key is held down;
+    /// use [`LayerHandle::on_key_with_repeat`] for that.
     pub fn on_key_press(
         &self,
         mut on_press: impl FnMut(LayerHandle, Keysym, Modifiers) + Send + 'static,
+    ) {
+        self.on_key(move |handle, event| {
+            if event.pressed {
+                on_press(handle, event.key, event.modifiers);
+            }
+        });
+    }
+
+    /// Do something on key release.
+    pub fn on_key_release(
+        &self,
+        mut on_release: impl FnMut(LayerHandle, Keysym, Modifiers) + Send + 'static,
+    ) {
+        self.on_key(move |handle, event| {
+            if !event.pressed {
+                on_release(handle, event.key, event.modifiers);
+            }
+        });
+    }
+
+    /// Do something on key presses and releases.
+    pub fn on_key(&self, on_key: impl FnMut(LayerHandle, KeyEvent) + Send + 'static) {
+        self.watch_keys(false, on_key);
+    }
+
+    /// Do something on key presses and releases, and repeatedly while a key is held down.
+    ///
+    /// Repeated presses have [`KeyEvent::repeat`] set.
+    pub fn on_key_with_repeat(&self, on_key: impl FnMut(LayerHandle, KeyEvent) + Send + 'static) {
+        self.watch_keys(true, on_key);
+    }
+
+    fn watch_keys(
+        &self,
+        repeat: bool,
+        mut on_key: impl FnMut(LayerHandle, KeyEvent) + Send + 'static,
     ) {
         let mut stream = match block_on_tokio(crate::input().keyboard_key(KeyboardKeyRequest {
             id: Some(self.id.into_inner()),
+            repeat: Some(repeat),
         })) {
             Ok(stream) => stream.into_inner(),
             Err(status) => {
-                error!("Failed to set `on_key_press` handler: {status}");
+                error!("Failed to set `on_key` handler: {status}");
                 return;
             }
         };
@@ -197,15 +992,89 @@ impl LayerHandle {
 
         tokio::spawn(async move {
             while let Some(Ok(response)) = stream.next().await {
-                if !response.pressed() {
-                    continue;
-                }
+                on_key(handle, response.into());
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                let key = Keysym::new(response.key());
-                let mods = Modifiers::from(response.modifiers.unwrap_or_default());
+    #[test]
+    fn layer_properties_only_set_what_was_asked_for() {
+        let properties = LayerProperties::new()
+            .size(200, 40)
+            .exclusive_zone(ExclusiveZone::Respect);
 
-                on_press(handle, key, mods);
+        assert_eq!(
+            properties,
+            LayerProperties {
+                width: Some(200),
+                height: Some(40),
+                exclusive_zone: Some(ExclusiveZone::Respect),
+                ..Default::default()
             }
-        });
+        );
+        assert_eq!(LayerProperties::new(), LayerProperties::default());
+    }
+
+    #[test]
+    fn output_selector_into_api() {
+        use layer::v0alpha1::new_layer_request::Output;
+
+        assert_eq!(
+            Output::from(OutputSelector::Name("DP-1".to_string())),
+            Output::OutputName("DP-1".to_string())
+        );
+        assert_eq!(
+            Output::from(OutputSelector::Description("Dell U2720Q".to_string())),
+            Output::OutputDescription("Dell U2720Q".to_string())
+        );
+    }
+
+    #[test]
+    fn per_output_selector_into_api() {
+        use layer::v0alpha1::new_layer_request::Output;
+
+        let filter = OutputFilter {
+            names: vec!["DP-1".to_string()],
+            description_contains: Some("Dell".to_string()),
+        };
+
+        assert_eq!(
+            Output::from(OutputSelector::PerOutput(filter)),
+            Output::PerOutput(layer::v0alpha1::OutputFilter {
+                names: vec!["DP-1".to_string()],
+                description_contains: Some("Dell".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn input_region_into_api() {
+        use layer::v0alpha1::input_region::{Rect, Rects, Region};
+
+        let rects = layer::v0alpha1::InputRegion::from(InputRegion::Rects(vec![(-5, 10, 100, 20)]));
+        assert_eq!(
+            rects.region,
+            Some(Region::Rects(Rects {
+                rects: vec![Rect {
+                    x: Some(-5),
+                    y: Some(10),
+                    width: Some(100),
+                    height: Some(20),
+                }],
+            }))
+        );
+
+        let automatic = layer::v0alpha1::InputRegion::from(InputRegion::Automatic);
+        assert_eq!(automatic.region, Some(Region::Automatic(())));
+
+        assert_eq!(
+            layer::v0alpha1::InputRegion::from(InputRegion::Full).region,
+            None
+        );
     }
 }
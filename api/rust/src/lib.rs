@@ -11,14 +11,24 @@
 //! of Snowcap are designed to be compositor-agnostic. You'll just need a compositor that
 //! implements the `wlr-layer-shell` protocol.
 
+// `NewLayerError` and `NewPopupError` carry a `tonic::Status` as is to keep their
+// public variants unchanged, which makes them larger than clippy would like.
+#![allow(clippy::result_large_err)]
+
 pub mod input;
 pub mod layer;
+pub mod output;
+pub mod popup;
 pub mod snowcap;
 pub mod widget;
 
+pub use snowcap_api_defs;
 use snowcap_api_defs::snowcap::{
     input::v0alpha1::input_service_client::InputServiceClient,
     layer::v0alpha1::layer_service_client::LayerServiceClient,
+    output::v0alpha1::output_service_client::OutputServiceClient,
+    popup::v0alpha1::popup_service_client::PopupServiceClient,
+    widget::v0alpha1::widget_service_client::WidgetServiceClient,
 };
 pub use xkbcommon;
 
@@ -31,6 +41,9 @@ use tower::service_fn;
 
 static LAYER: OnceLock<LayerServiceClient<Channel>> = OnceLock::new();
 static INPUT: OnceLock<InputServiceClient<Channel>> = OnceLock::new();
+static OUTPUT: OnceLock<OutputServiceClient<Channel>> = OnceLock::new();
+static POPUP: OnceLock<PopupServiceClient<Channel>> = OnceLock::new();
+static WIDGET: OnceLock<WidgetServiceClient<Channel>> = OnceLock::new();
 
 pub(crate) fn layer() -> LayerServiceClient<Channel> {
     LAYER
@@ -44,6 +57,24 @@ pub(crate) fn input() -> InputServiceClient<Channel> {
         .expect("grpc connection was not initialized")
         .clone()
 }
+pub(crate) fn output() -> OutputServiceClient<Channel> {
+    OUTPUT
+        .get()
+        .expect("grpc connection was not initialized")
+        .clone()
+}
+pub(crate) fn popup() -> PopupServiceClient<Channel> {
+    POPUP
+        .get()
+        .expect("grpc connection was not initialized")
+        .clone()
+}
+pub(crate) fn widget() -> WidgetServiceClient<Channel> {
+    WIDGET
+        .get()
+        .expect("grpc connection was not initialized")
+        .clone()
+}
 
 fn socket_dir() -> PathBuf {
     xdg::BaseDirectories::with_prefix("snowcap")
@@ -70,6 +101,9 @@ pub async fn connect() -> Result<Layer, Box<dyn std::error::Error>> {
 
     let _ = LAYER.set(LayerServiceClient::new(channel.clone()));
     let _ = INPUT.set(InputServiceClient::new(channel.clone()));
+    let _ = OUTPUT.set(OutputServiceClient::new(channel.clone()));
+    let _ = POPUP.set(PopupServiceClient::new(channel.clone()));
+    let _ = WIDGET.set(WidgetServiceClient::new(channel.clone()));
 
     Ok(Layer)
 }
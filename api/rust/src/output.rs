@@ -0,0 +1,157 @@
+//! Output information and hotplug events.
+
+use snowcap_api_defs::snowcap::output::{
+    self,
+    v0alpha1::{watch_outputs_response, ListOutputsRequest, WatchOutputsRequest},
+};
+use tokio_stream::StreamExt;
+use tracing::error;
+
+use crate::block_on_tokio;
+
+/// A transform applied to an output.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Transform {
+    #[default]
+    Normal,
+    _90,
+    _180,
+    _270,
+    Flipped,
+    Flipped90,
+    Flipped180,
+    Flipped270,
+}
+
+impl From<output::v0alpha1::Transform> for Transform {
+    fn from(value: output::v0alpha1::Transform) -> Self {
+        match value {
+            output::v0alpha1::Transform::Unspecified | output::v0alpha1::Transform::Normal => {
+                Self::Normal
+            }
+            output::v0alpha1::Transform::Transform90 => Self::_90,
+            output::v0alpha1::Transform::Transform180 => Self::_180,
+            output::v0alpha1::Transform::Transform270 => Self::_270,
+            output::v0alpha1::Transform::Flipped => Self::Flipped,
+            output::v0alpha1::Transform::Flipped90 => Self::Flipped90,
+            output::v0alpha1::Transform::Flipped180 => Self::Flipped180,
+            output::v0alpha1::Transform::Flipped270 => Self::Flipped270,
+        }
+    }
+}
+
+/// An output mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Mode {
+    /// The width of the mode, in physical pixels.
+    pub width: i32,
+    /// The height of the mode, in physical pixels.
+    pub height: i32,
+    /// The refresh rate in millihertz.
+    pub refresh_rate: i32,
+}
+
+/// Information about an output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutputInfo {
+    /// An id that identifies this output for as long as it is connected.
+    pub id: u32,
+    /// The output's name, like "DP-1".
+    pub name: Option<String>,
+    /// The output's human-readable description.
+    pub description: Option<String>,
+    /// The output's make.
+    pub make: String,
+    /// The output's model.
+    pub model: String,
+    /// The position of the output's top-left corner in the global compositor space.
+    pub logical_position: Option<(i32, i32)>,
+    /// The size of the output in the global compositor space.
+    pub logical_size: Option<(i32, i32)>,
+    /// The output's integer scale.
+    pub scale: i32,
+    /// The output's transform.
+    pub transform: Transform,
+    /// The output's current mode.
+    pub current_mode: Option<Mode>,
+}
+
+impl From<output::v0alpha1::OutputInfo> for OutputInfo {
+    fn from(value: output::v0alpha1::OutputInfo) -> Self {
+        let logical_position = value.logical_x.zip(value.logical_y);
+        let logical_size = value.logical_width.zip(value.logical_height);
+
+        Self {
+            id: value.id(),
+            transform: value.transform().into(),
+            scale: value.scale(),
+            make: value.make().to_string(),
+            model: value.model().to_string(),
+            name: value.name,
+            description: value.description,
+            logical_position,
+            logical_size,
+            current_mode: value.current_mode.map(|mode| Mode {
+                width: mode.width(),
+                height: mode.height(),
+                refresh_rate: mode.refresh_rate(),
+            }),
+        }
+    }
+}
+
+/// A change to the set of connected outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OutputEvent {
+    /// A new output was connected.
+    Added(OutputInfo),
+    /// An output's properties changed.
+    Changed(OutputInfo),
+    /// An output was disconnected. Contains the output's last known state.
+    Removed(OutputInfo),
+}
+
+/// Get information about all currently connected outputs.
+pub fn outputs() -> Vec<OutputInfo> {
+    match block_on_tokio(crate::output().list_outputs(ListOutputsRequest {})) {
+        Ok(response) => response
+            .into_inner()
+            .outputs
+            .into_iter()
+            .map(OutputInfo::from)
+            .collect(),
+        Err(status) => {
+            error!("Failed to list outputs: {status}");
+            Vec::new()
+        }
+    }
+}
+
+/// Do something when an output is connected, changed, or disconnected.
+pub fn on_output_event(mut on_event: impl FnMut(OutputEvent) + Send + 'static) {
+    let mut stream = match block_on_tokio(crate::output().watch_outputs(WatchOutputsRequest {})) {
+        Ok(stream) => stream.into_inner(),
+        Err(status) => {
+            error!("Failed to set `on_output_event` handler: {status}");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while let Some(Ok(response)) = stream.next().await {
+            let event = match response.event {
+                Some(watch_outputs_response::Event::Added(info)) => OutputEvent::Added(info.into()),
+                Some(watch_outputs_response::Event::Changed(info)) => {
+                    OutputEvent::Changed(info.into())
+                }
+                Some(watch_outputs_response::Event::Removed(info)) => {
+                    OutputEvent::Removed(info.into())
+                }
+                None => continue,
+            };
+
+            on_event(event);
+        }
+    });
+}
@@ -0,0 +1,288 @@
+//! Support for popups attached to layers using `xdg_popup`.
+//!
+//! Popups are created with [`LayerHandle::new_popup`][crate::layer::LayerHandle::new_popup]
+//! or [`PopupHandle::new_popup`] and are useful for menus and tooltips.
+
+use snowcap_api_defs::snowcap::popup::{
+    self,
+    v0alpha1::{
+        watch_popup_response, ClosePopupRequest, NewPopupRequest, UpdatePopupRequest,
+        WatchPopupRequest,
+    },
+};
+use tokio_stream::StreamExt;
+use tracing::error;
+
+use crate::{
+    block_on_tokio,
+    widget::{WidgetDef, WidgetId},
+};
+
+/// A point on a popup's anchor rectangle, or the direction a popup extends in.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Edge {
+    #[default]
+    None,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+impl From<Edge> for popup::v0alpha1::Edge {
+    fn from(value: Edge) -> Self {
+        match value {
+            Edge::None => Self::None,
+            Edge::Top => Self::Top,
+            Edge::Bottom => Self::Bottom,
+            Edge::Left => Self::Left,
+            Edge::Right => Self::Right,
+            Edge::TopLeft => Self::TopLeft,
+            Edge::BottomLeft => Self::BottomLeft,
+            Edge::TopRight => Self::TopRight,
+            Edge::BottomRight => Self::BottomRight,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// How the compositor may adjust a popup that would end up off-screen.
+    #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+    pub struct ConstraintAdjustment: u32 {
+        /// Slide the popup horizontally.
+        const SLIDE_X = 1;
+        /// Slide the popup vertically.
+        const SLIDE_Y = 2;
+        /// Flip the anchor and gravity horizontally.
+        const FLIP_X = 4;
+        /// Flip the anchor and gravity vertically.
+        const FLIP_Y = 8;
+        /// Shrink the popup horizontally.
+        const RESIZE_X = 16;
+        /// Shrink the popup vertically.
+        const RESIZE_Y = 32;
+    }
+}
+
+/// Where a popup is placed relative to its parent.
+///
+/// The popup is placed at the `anchor` point of `anchor_rect`, extending in the
+/// direction of `gravity`, then moved by `offset`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// The area of the parent the popup is positioned relative to,
+    /// as `(x, y, width, height)` in logical coordinates.
+    ///
+    /// If `None`, this is the parent's top-left pixel.
+    pub anchor_rect: Option<(i32, i32, u32, u32)>,
+    /// The point on the anchor rect the popup is placed at.
+    pub anchor: Edge,
+    /// The direction the popup extends in from the anchor point.
+    pub gravity: Edge,
+    /// How the compositor may move or resize the popup to keep it on-screen.
+    pub constraint_adjustment: ConstraintAdjustment,
+    /// An offset from the anchor point.
+    pub offset: (i32, i32),
+}
+
+/// The error type for [`LayerHandle::new_popup`][crate::layer::LayerHandle::new_popup]
+/// and [`PopupHandle::new_popup`].
+#[derive(thiserror::Error, Debug)]
+pub enum NewPopupError {
+    /// Snowcap returned a gRPC error status.
+    #[error("gRPC error: `{0}`")]
+    GrpcStatus(#[from] tonic::Status),
+    /// Snowcap did not return a popup id as expected.
+    #[error("snowcap did not return a popup id")]
+    NoPopupId,
+}
+
+pub(crate) fn new_popup(
+    parent: WidgetId,
+    widget: WidgetDef,
+    width: u32,
+    height: u32,
+    position: Position,
+    grab: bool,
+) -> Result<PopupHandle, NewPopupError> {
+    let Position {
+        anchor_rect,
+        anchor,
+        gravity,
+        constraint_adjustment,
+        offset,
+    } = position;
+
+    let response = block_on_tokio(crate::popup().new_popup(NewPopupRequest {
+        parent_id: Some(parent.into_inner()),
+        widget_def: Some(widget.into()),
+        width: Some(width),
+        height: Some(height),
+        anchor_rect: anchor_rect.map(|(x, y, width, height)| popup::v0alpha1::Rect {
+            x: Some(x),
+            y: Some(y),
+            width: Some(width),
+            height: Some(height),
+        }),
+        anchor: Some(popup::v0alpha1::Edge::from(anchor) as i32),
+        gravity: Some(popup::v0alpha1::Edge::from(gravity) as i32),
+        constraint_adjustment: Some(constraint_adjustment.bits()),
+        offset_x: Some(offset.0),
+        offset_y: Some(offset.1),
+        grab: Some(grab),
+    }))?;
+
+    let id = response
+        .into_inner()
+        .popup_id
+        .ok_or(NewPopupError::NoPopupId)?;
+
+    Ok(PopupHandle { id: id.into() })
+}
+
+/// Why a popup was closed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PopupCloseReason {
+    /// The compositor dismissed the popup, for example because of a click outside of it.
+    Dismissed,
+    /// A client or the popup's widgets asked for the popup to be closed.
+    Request,
+    /// The layer or popup this popup is attached to was closed.
+    ParentClosed,
+    /// Snowcap didn't say why.
+    Unknown,
+}
+
+impl From<popup::v0alpha1::PopupCloseReason> for PopupCloseReason {
+    fn from(value: popup::v0alpha1::PopupCloseReason) -> Self {
+        match value {
+            popup::v0alpha1::PopupCloseReason::Unspecified => Self::Unknown,
+            popup::v0alpha1::PopupCloseReason::Dismissed => Self::Dismissed,
+            popup::v0alpha1::PopupCloseReason::Request => Self::Request,
+            popup::v0alpha1::PopupCloseReason::ParentClosed => Self::ParentClosed,
+        }
+    }
+}
+
+/// Something that happened to a popup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PopupEvent {
+    /// The compositor placed the popup.
+    Configured {
+        /// The x position relative to the parent.
+        x: i32,
+        /// The y position relative to the parent.
+        y: i32,
+        /// The width.
+        width: u32,
+        /// The height.
+        height: u32,
+    },
+    /// The popup was closed.
+    Closed(PopupCloseReason),
+}
+
+impl From<watch_popup_response::Event> for PopupEvent {
+    fn from(value: watch_popup_response::Event) -> Self {
+        match value {
+            watch_popup_response::Event::Configured(configured) => Self::Configured {
+                x: configured.x(),
+                y: configured.y(),
+                width: configured.width(),
+                height: configured.height(),
+            },
+            watch_popup_response::Event::Closed(closed) => Self::Closed(closed.reason().into()),
+        }
+    }
+}
+
+/// A handle to a popup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PopupHandle {
+    id: WidgetId,
+}
+
+impl From<PopupHandle> for WidgetId {
+    fn from(value: PopupHandle) -> Self {
+        value.id
+    }
+}
+
+impl PopupHandle {
+    /// Create a popup attached to this popup, like a submenu.
+    ///
+    /// See [`LayerHandle::new_popup`][crate::layer::LayerHandle::new_popup].
+    pub fn new_popup(
+        &self,
+        widget: impl Into<WidgetDef>,
+        width: u32,
+        height: u32,
+        position: Position,
+        grab: bool,
+    ) -> Result<PopupHandle, NewPopupError> {
+        new_popup(self.id, widget.into(), width, height, position, grab)
+    }
+
+    /// Close this popup and any popups attached to it.
+    pub fn close(&self) {
+        if let Err(status) = block_on_tokio(crate::popup().close(ClosePopupRequest {
+            popup_id: Some(self.id.into_inner()),
+        })) {
+            error!("Failed to close {self:?}: {status}");
+        }
+    }
+
+    /// Replace this popup's widget tree with a new one.
+    pub fn update(&self, widget: impl Into<WidgetDef>) {
+        if let Err(status) = block_on_tokio(crate::popup().update_popup(UpdatePopupRequest {
+            popup_id: Some(self.id.into_inner()),
+            widget_def: Some(widget.into().into()),
+        })) {
+            error!("Failed to update {self:?}: {status}");
+        }
+    }
+
+    /// Do something when this popup is closed.
+    ///
+    /// After this, the handle no longer refers to a live popup.
+    pub fn on_close(
+        &self,
+        mut on_close: impl FnMut(PopupHandle, PopupCloseReason) + Send + 'static,
+    ) {
+        self.on_event(move |handle, event| {
+            if let PopupEvent::Closed(reason) = event {
+                on_close(handle, reason);
+            }
+        });
+    }
+
+    /// Do something whenever anything happens to this popup.
+    pub fn on_event(&self, mut on_event: impl FnMut(PopupHandle, PopupEvent) + Send + 'static) {
+        let mut stream = match block_on_tokio(crate::popup().watch_popup(WatchPopupRequest {
+            popup_id: Some(self.id.into_inner()),
+        })) {
+            Ok(stream) => stream.into_inner(),
+            Err(status) => {
+                error!("Failed to watch popup: {status}");
+                return;
+            }
+        };
+
+        let handle = *self;
+
+        tokio::spawn(async move {
+            while let Some(Ok(response)) = stream.next().await {
+                let Some(event) = response.event else {
+                    continue;
+                };
+
+                on_event(handle, event.into());
+            }
+        });
+    }
+}
@@ -5,7 +5,14 @@
 pub mod font;
 
 use font::Font;
-use snowcap_api_defs::snowcap::widget;
+use snowcap_api_defs::snowcap::widget::{
+    self,
+    v0alpha1::{watch_widget_events_response, WatchWidgetEventsRequest},
+};
+use tokio_stream::StreamExt;
+use tracing::error;
+
+use crate::block_on_tokio;
 
 /// A unique identifier for a widget.
 #[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
@@ -33,6 +40,8 @@ pub enum WidgetDef {
     Row(Row),
     Scrollable(Box<Scrollable>),
     Container(Box<Container>),
+    Button(Box<Button>),
+    TextInput(TextInput),
 }
 
 impl From<Scrollable> for WidgetDef {
@@ -47,6 +56,12 @@ impl From<Container> for WidgetDef {
     }
 }
 
+impl From<Button> for WidgetDef {
+    fn from(value: Button) -> Self {
+        Self::Button(Box::new(value))
+    }
+}
+
 impl From<WidgetDef> for widget::v0alpha1::WidgetDef {
     fn from(value: WidgetDef) -> widget::v0alpha1::WidgetDef {
         widget::v0alpha1::WidgetDef {
@@ -62,6 +77,12 @@ impl From<WidgetDef> for widget::v0alpha1::WidgetDef {
                 WidgetDef::Container(container) => {
                     widget::v0alpha1::widget_def::Widget::Container(Box::new((*container).into()))
                 }
+                WidgetDef::Button(button) => {
+                    widget::v0alpha1::widget_def::Widget::Button(Box::new((*button).into()))
+                }
+                WidgetDef::TextInput(text_input) => {
+                    widget::v0alpha1::widget_def::Widget::TextInput(text_input.into())
+                }
             }),
         }
     }
@@ -584,6 +605,10 @@ pub struct Container {
     pub border_radius: Option<f32>,
     pub border_thickness: Option<f32>,
     pub border_color: Option<Color>,
+
+    /// Whether this container accepts pointer input when the layer's input region
+    /// is [`InputRegion::Automatic`][crate::layer::InputRegion::Automatic].
+    pub interactive: Option<bool>,
 }
 
 impl Container {
@@ -603,6 +628,7 @@ impl Container {
             border_radius: None,
             border_thickness: None,
             border_color: None,
+            interactive: None,
         }
     }
 
@@ -696,6 +722,13 @@ impl Container {
             ..self
         }
     }
+
+    pub fn interactive(self, interactive: bool) -> Self {
+        Self {
+            interactive: Some(interactive),
+            ..self
+        }
+    }
 }
 
 impl From<Container> for widget::v0alpha1::Container {
@@ -719,6 +752,340 @@ impl From<Container> for widget::v0alpha1::Container {
             border_radius: value.border_radius,
             border_thickness: value.border_thickness,
             border_color: value.border_color.map(From::from),
+            interactive: value.interactive,
+        }
+    }
+}
+
+/// The style of a [`Button`]. Unset fields use the theme's style.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ButtonStyle {
+    pub text_color: Option<Color>,
+    pub background_color: Option<Color>,
+    pub border_radius: Option<f32>,
+    pub border_thickness: Option<f32>,
+    pub border_color: Option<Color>,
+}
+
+impl From<ButtonStyle> for widget::v0alpha1::ButtonStyle {
+    fn from(value: ButtonStyle) -> Self {
+        widget::v0alpha1::ButtonStyle {
+            text_color: value.text_color.map(From::from),
+            background_color: value.background_color.map(From::from),
+            border_radius: value.border_radius,
+            border_thickness: value.border_thickness,
+            border_color: value.border_color.map(From::from),
+        }
+    }
+}
+
+/// A clickable button.
+///
+/// Presses are only reported for buttons with an [`id`][Button::id],
+/// and can be handled with [`on_press`]. Buttons without an id are disabled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Button {
+    pub child: WidgetDef,
+    pub padding: Option<Padding>,
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub style: Option<ButtonStyle>,
+    /// Overrides `style` while the pointer is over the button.
+    pub hovered_style: Option<ButtonStyle>,
+    /// Overrides `style` and `hovered_style` while the button is being pressed.
+    pub pressed_style: Option<ButtonStyle>,
+    pub id: Option<u32>,
+}
+
+impl Button {
+    pub fn new(child: impl Into<WidgetDef>) -> Self {
+        Self {
+            child: child.into(),
+            padding: None,
+            width: None,
+            height: None,
+            style: None,
+            hovered_style: None,
+            pressed_style: None,
+            id: None,
+        }
+    }
+
+    pub fn padding(self, padding: Padding) -> Self {
+        Self {
+            padding: Some(padding),
+            ..self
+        }
+    }
+
+    pub fn width(self, width: Length) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    pub fn height(self, height: Length) -> Self {
+        Self {
+            height: Some(height),
+            ..self
+        }
+    }
+
+    pub fn style(self, style: ButtonStyle) -> Self {
+        Self {
+            style: Some(style),
+            ..self
+        }
+    }
+
+    pub fn hovered_style(self, style: ButtonStyle) -> Self {
+        Self {
+            hovered_style: Some(style),
+            ..self
+        }
+    }
+
+    pub fn pressed_style(self, style: ButtonStyle) -> Self {
+        Self {
+            pressed_style: Some(style),
+            ..self
+        }
+    }
+
+    /// Set the id this button is told apart by in [`on_press`].
+    pub fn id(self, id: u32) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+}
+
+impl From<Button> for widget::v0alpha1::Button {
+    fn from(value: Button) -> Self {
+        widget::v0alpha1::Button {
+            child: Some(Box::new(value.child.into())),
+            padding: value.padding.map(From::from),
+            width: value.width.map(From::from),
+            height: value.height.map(From::from),
+            style: value.style.map(From::from),
+            hovered_style: value.hovered_style.map(From::from),
+            pressed_style: value.pressed_style.map(From::from),
+            widget_id: value.id,
+        }
+    }
+}
+
+/// A single-line text field.
+///
+/// Snowcap keeps track of what is typed into it. When the widget tree is updated,
+/// the typed text is kept unless [`value`][TextInput::value] changed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextInput {
+    /// Text shown while the input is empty.
+    pub placeholder: String,
+    /// The text the input starts with.
+    pub value: Option<String>,
+    pub font: Option<Font>,
+    pub size: Option<f32>,
+    pub padding: Option<Padding>,
+    pub width: Option<Length>,
+    /// Hide what is typed, for passwords.
+    pub password: bool,
+    pub id: Option<u32>,
+}
+
+impl TextInput {
+    pub fn new(placeholder: impl ToString) -> Self {
+        Self {
+            placeholder: placeholder.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn value(self, value: impl ToString) -> Self {
+        Self {
+            value: Some(value.to_string()),
+            ..self
+        }
+    }
+
+    pub fn font(self, font: Font) -> Self {
+        Self {
+            font: Some(font),
+            ..self
+        }
+    }
+
+    pub fn size(self, size: f32) -> Self {
+        Self {
+            size: Some(size),
+            ..self
+        }
+    }
+
+    pub fn padding(self, padding: Padding) -> Self {
+        Self {
+            padding: Some(padding),
+            ..self
+        }
+    }
+
+    pub fn width(self, width: Length) -> Self {
+        Self {
+            width: Some(width),
+            ..self
         }
     }
+
+    pub fn password(self, password: bool) -> Self {
+        Self { password, ..self }
+    }
+
+    /// Set the id this input is told apart by in [`on_text_changed`] and [`on_text_submitted`].
+    pub fn id(self, id: u32) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+}
+
+impl From<TextInput> for widget::v0alpha1::TextInput {
+    fn from(value: TextInput) -> Self {
+        widget::v0alpha1::TextInput {
+            placeholder: Some(value.placeholder),
+            value: value.value,
+            font: value.font.map(From::from),
+            pixels: value.size,
+            padding: value.padding.map(From::from),
+            width: value.width.map(From::from),
+            password: Some(value.password),
+            widget_id: value.id,
+        }
+    }
+}
+
+fn watch_widget_events(
+    parent: WidgetId,
+    handler_name: &str,
+    mut on_event: impl FnMut(u32, watch_widget_events_response::Event) + Send + 'static,
+) {
+    let mut stream = match block_on_tokio(crate::widget().watch_widget_events(
+        WatchWidgetEventsRequest {
+            id: Some(parent.into_inner()),
+        },
+    )) {
+        Ok(stream) => stream.into_inner(),
+        Err(status) => {
+            error!("Failed to set `{handler_name}` handler: {status}");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while let Some(Ok(response)) = stream.next().await {
+            let widget_id = response.widget_id();
+            if let Some(event) = response.event {
+                on_event(widget_id, event);
+            }
+        }
+    });
+}
+
+/// Do something when the [`Button`] with the given id is pressed.
+///
+/// `parent` is the layer, layer group, or popup the button is in, for example
+/// a [`LayerHandle`][crate::layer::LayerHandle] or [`PopupHandle`][crate::popup::PopupHandle].
+/// For layer groups, presses from every instance are received.
+pub fn on_press(
+    parent: impl Into<WidgetId>,
+    button_id: u32,
+    mut on_press: impl FnMut() + Send + 'static,
+) {
+    watch_widget_events(parent.into(), "on_press", move |widget_id, event| {
+        if let watch_widget_events_response::Event::ButtonPressed(_) = event {
+            if widget_id == button_id {
+                on_press();
+            }
+        }
+    });
+}
+
+/// Do something with the new text whenever the text in the [`TextInput`]
+/// with the given id changes.
+///
+/// See [`on_press`] for what `parent` is.
+pub fn on_text_changed(
+    parent: impl Into<WidgetId>,
+    input_id: u32,
+    mut on_text_changed: impl FnMut(String) + Send + 'static,
+) {
+    watch_widget_events(parent.into(), "on_text_changed", move |widget_id, event| {
+        if let watch_widget_events_response::Event::TextChanged(changed) = event {
+            if widget_id == input_id {
+                on_text_changed(changed.text.unwrap_or_default());
+            }
+        }
+    });
+}
+
+/// Do something with the text when Enter is pressed in the [`TextInput`]
+/// with the given id.
+///
+/// See [`on_press`] for what `parent` is.
+pub fn on_text_submitted(
+    parent: impl Into<WidgetId>,
+    input_id: u32,
+    mut on_text_submitted: impl FnMut(String) + Send + 'static,
+) {
+    watch_widget_events(
+        parent.into(),
+        "on_text_submitted",
+        move |widget_id, event| {
+            if let watch_widget_events_response::Event::TextSubmitted(submitted) = event {
+                if widget_id == input_id {
+                    on_text_submitted(submitted.text.unwrap_or_default());
+                }
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_input_into_api() {
+        let text_input = TextInput::new("Search")
+            .value("snow")
+            .size(14.0)
+            .password(true)
+            .id(3);
+
+        assert_eq!(
+            widget::v0alpha1::TextInput::from(text_input),
+            widget::v0alpha1::TextInput {
+                placeholder: Some("Search".to_string()),
+                value: Some("snow".to_string()),
+                font: None,
+                pixels: Some(14.0),
+                padding: None,
+                width: None,
+                password: Some(true),
+                widget_id: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn text_input_without_a_value_leaves_it_unset() {
+        let text_input = widget::v0alpha1::TextInput::from(TextInput::new(""));
+
+        assert_eq!(text_input.value, None);
+        assert_eq!(text_input.widget_id, None);
+        assert_eq!(text_input.password, Some(false));
+    }
 }
@@ -17,6 +17,18 @@ pub mod snowcap {
         }
     }
 
+    pub mod output {
+        pub mod v0alpha1 {
+            tonic::include_proto!("snowcap.output.v0alpha1");
+        }
+    }
+
+    pub mod popup {
+        pub mod v0alpha1 {
+            tonic::include_proto!("snowcap.popup.v0alpha1");
+        }
+    }
+
     pub mod input {
         pub mod v0alpha1 {
             tonic::include_proto!("snowcap.input.v0alpha1");
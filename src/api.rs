@@ -1,21 +1,45 @@
+// tonic requires `Status` as the error type of gRPC handlers, and the results
+// of the closures in these services are handed straight back to it.
+#![allow(clippy::result_large_err)]
+
 pub mod input;
+pub mod output;
+pub mod popup;
+pub mod widget;
 
-use std::{num::NonZeroU32, pin::Pin};
+use std::{pin::Pin, time::Duration};
 
 use futures::Stream;
-use smithay_client_toolkit::{reexports::calloop, shell::wlr_layer};
+use smithay_client_toolkit::{
+    reexports::{calloop, client::Proxy},
+    shell::{
+        wlr_layer::{self, SurfaceKind},
+        WaylandSurface,
+    },
+};
 use snowcap_api_defs::snowcap::layer::{
     self,
-    v0alpha1::{layer_service_server, CloseRequest, NewLayerRequest, NewLayerResponse},
+    v0alpha1::{
+        layer_service_server, new_layer_request, CloseReason, CloseRequest, GetLayerRequest,
+        GetLayerResponse, HideReason, HideRequest, ListLayersRequest, ListLayersResponse,
+        NewLayerRequest, NewLayerResponse, SetLayerPropertiesRequest, ShowRequest,
+        UpdateLayerRequest, WatchLayerRequest, WatchLayerResponse,
+    },
 };
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use tonic::{Request, Response, Status};
 use tracing::warn;
 
 use crate::{
-    layer::{ExclusiveZone, SnowcapLayer},
+    layer::{
+        group::{LayerGroup, LayerTemplate, OutputFilter},
+        timeout::{LayerTimeout, TimeoutSettings},
+        ExclusiveZone, InputRegion, Margin, OutputSelector, SnowcapLayer,
+    },
+    server::ClientInfo,
     state::State,
-    widget::widget_def_to_fn,
+    util::convert::{FromApi, ToApi},
+    widget::{widget_def_to_fn, SnowcapMessage, SnowcapWidgetProgram, WidgetId},
 };
 
 async fn run_unary_no_response<F>(
@@ -60,29 +84,46 @@ where
     }
 }
 
-fn run_server_streaming<F, T>(
+/// Run `with_state` with a sender for a new response stream.
+///
+/// If it returns an error, that is returned to the client instead of the stream.
+async fn run_server_streaming<F, T>(
     fn_sender: &StateFnSender,
     with_state: F,
 ) -> Result<Response<ResponseStream<T>>, Status>
 where
-    F: FnOnce(&mut State, UnboundedSender<Result<T, Status>>) + Send + 'static,
+    F: FnOnce(&mut State, UnboundedSender<Result<T, Status>>) -> Result<(), Status>
+        + Send
+        + 'static,
     T: Send + 'static,
 {
     let (sender, receiver) = unbounded_channel::<Result<T, Status>>();
+    let (result_sender, result_receiver) = tokio::sync::oneshot::channel::<Result<(), Status>>();
 
     let f = Box::new(|state: &mut State| {
-        with_state(state, sender);
+        if result_sender.send(with_state(state, sender)).is_err() {
+            warn!("failed to send result of API call to config; receiver already dropped");
+        }
     });
 
     fn_sender
         .send(f)
         .map_err(|_| Status::internal("failed to execute request"))?;
 
+    match result_receiver.await {
+        Ok(result) => result?,
+        Err(err) => {
+            return Err(Status::internal(format!(
+                "failed to transfer response for transport to client: {err}"
+            )))
+        }
+    }
+
     let receiver_stream = tokio_stream::wrappers::UnboundedReceiverStream::new(receiver);
     Ok(Response::new(Box::pin(receiver_stream)))
 }
 
-type StateFnSender = calloop::channel::Sender<Box<dyn FnOnce(&mut State) + Send>>;
+pub type StateFnSender = calloop::channel::Sender<Box<dyn FnOnce(&mut State) + Send>>;
 
 type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
 
@@ -108,72 +149,107 @@ impl LayerService {
 
 #[tonic::async_trait]
 impl layer_service_server::LayerService for LayerService {
+    type WatchLayerStream = ResponseStream<WatchLayerResponse>;
+
     async fn new_layer(
         &self,
         request: Request<NewLayerRequest>,
     ) -> Result<Response<NewLayerResponse>, Status> {
+        let owner = request.extensions().get::<ClientInfo>().copied();
         let request = request.into_inner();
 
-        let anchor = request.anchor();
+        let keep_on_disconnect = request.keep_on_disconnect();
+        let auto_size = request.auto_size();
+        let timeout = request.timeout_ms.map(|ms| TimeoutSettings {
+            duration: Duration::from_millis(ms as u64),
+            pause_on_hover: request.pause_timeout_on_hover(),
+            hide: request.hide_on_timeout(),
+        });
+        let anchor = match request.anchor_flags {
+            Some(flags) => wlr_layer::Anchor::from_bits_truncate(flags),
+            None => wlr_layer::Anchor::from_api(request.anchor()),
+        };
         let exclusive_zone = request.exclusive_zone();
         let keyboard_interactivity = request.keyboard_interactivity();
         let layer = request.layer();
+        let input_region = request
+            .input_region
+            .map(InputRegion::from_api)
+            .unwrap_or_default();
 
         let Some(widget_def) = request.widget_def else {
             return Err(Status::invalid_argument("no widget def"));
         };
+        if widget_def.widget.is_none() {
+            return Err(Status::invalid_argument("widget def was null"));
+        }
 
         let width = request.width.unwrap_or(600);
         let height = request.height.unwrap_or(480);
-
-        let anchor = match anchor {
-            layer::v0alpha1::Anchor::Unspecified => wlr_layer::Anchor::empty(),
-            layer::v0alpha1::Anchor::Top => wlr_layer::Anchor::TOP,
-            layer::v0alpha1::Anchor::Bottom => wlr_layer::Anchor::BOTTOM,
-            layer::v0alpha1::Anchor::Left => wlr_layer::Anchor::LEFT,
-            layer::v0alpha1::Anchor::Right => wlr_layer::Anchor::RIGHT,
-            layer::v0alpha1::Anchor::TopLeft => wlr_layer::Anchor::TOP | wlr_layer::Anchor::LEFT,
-            layer::v0alpha1::Anchor::TopRight => wlr_layer::Anchor::TOP | wlr_layer::Anchor::RIGHT,
-            layer::v0alpha1::Anchor::BottomLeft => {
-                wlr_layer::Anchor::BOTTOM | wlr_layer::Anchor::LEFT
+        let margin = request.margin.map(Margin::from_api).unwrap_or_default();
+        let namespace = request.namespace.unwrap_or_else(|| String::from("snowcap"));
+
+        let exclusive_zone = ExclusiveZone::from_api(exclusive_zone);
+        let keyboard_interactivity =
+            wlr_layer::KeyboardInteractivity::from_api(keyboard_interactivity);
+        let layer = wlr_layer::Layer::from_api(layer);
+
+        let output = match request.output {
+            Some(new_layer_request::Output::PerOutput(filter)) => {
+                let filter = OutputFilter::from_api(filter);
+                let template = LayerTemplate {
+                    width,
+                    height,
+                    layer,
+                    anchor,
+                    exclusive_zone,
+                    keyboard_interactivity,
+                    margin,
+                    namespace,
+                    auto_size,
+                    input_region,
+                    timeout,
+                    hidden: false,
+                    widget_def,
+                    owner,
+                    keep_on_disconnect,
+                };
+
+                return run_unary(&self.sender, move |state| {
+                    let group_id = state.widget_id_counter.next_and_increment();
+                    state
+                        .layer_groups
+                        .push(LayerGroup::new(group_id, filter, template));
+
+                    let outputs = state.output_state.outputs().collect::<Vec<_>>();
+                    for output in outputs {
+                        state.sync_layer_groups(&output);
+                    }
+
+                    Ok(NewLayerResponse {
+                        layer_id: Some(group_id.into_inner()),
+                    })
+                })
+                .await;
             }
-            layer::v0alpha1::Anchor::BottomRight => {
-                wlr_layer::Anchor::BOTTOM | wlr_layer::Anchor::RIGHT
+            Some(new_layer_request::Output::OutputName(name)) => Some(OutputSelector::Name(name)),
+            Some(new_layer_request::Output::OutputDescription(desc)) => {
+                Some(OutputSelector::Description(desc))
             }
-        };
-        let exclusive_zone = match exclusive_zone {
-            0 => ExclusiveZone::Respect,
-            x if x.is_positive() => ExclusiveZone::Exclusive(NonZeroU32::new(x as u32).unwrap()),
-            _ => ExclusiveZone::Ignore,
-        };
-
-        let keyboard_interactivity = match keyboard_interactivity {
-            layer::v0alpha1::KeyboardInteractivity::Unspecified
-            | layer::v0alpha1::KeyboardInteractivity::None => {
-                wlr_layer::KeyboardInteractivity::None
-            }
-            layer::v0alpha1::KeyboardInteractivity::OnDemand => {
-                wlr_layer::KeyboardInteractivity::OnDemand
-            }
-            layer::v0alpha1::KeyboardInteractivity::Exclusive => {
-                wlr_layer::KeyboardInteractivity::Exclusive
-            }
-        };
-
-        let layer = match layer {
-            layer::v0alpha1::Layer::Unspecified => wlr_layer::Layer::Top,
-            layer::v0alpha1::Layer::Background => wlr_layer::Layer::Background,
-            layer::v0alpha1::Layer::Bottom => wlr_layer::Layer::Bottom,
-            layer::v0alpha1::Layer::Top => wlr_layer::Layer::Top,
-            layer::v0alpha1::Layer::Overlay => wlr_layer::Layer::Overlay,
+            None => None,
         };
 
         run_unary(&self.sender, move |state| {
-            let Some((f, states)) = widget_def_to_fn(widget_def) else {
-                return Err(Status::invalid_argument("widget def was null"));
+            let output = match output {
+                Some(selector) => Some(
+                    selector
+                        .resolve(&state.output_state)
+                        .ok_or_else(|| Status::not_found(format!("no output with {selector}")))?,
+                ),
+                None => None,
             };
 
-            let layer = SnowcapLayer::new(
+            let mut layer = SnowcapLayer::new(
                 state,
                 width,
                 height,
@@ -181,11 +257,19 @@ impl layer_service_server::LayerService for LayerService {
                 anchor,
                 exclusive_zone,
                 keyboard_interactivity,
-                crate::widget::SnowcapWidgetProgram {
-                    widgets: f,
-                    widget_state: states,
-                },
-            );
+                margin,
+                output,
+                namespace,
+                auto_size,
+                widget_def,
+            )
+            .map_err(|err| Status::internal(format!("{err:#}")))?;
+            layer.owner = owner;
+            layer.keep_on_disconnect = keep_on_disconnect;
+            layer.input_region = input_region;
+            layer.timeout = timeout.map(|settings| {
+                LayerTimeout::new(state.loop_handle.clone(), layer.widget_id, settings)
+            });
 
             let ret = Ok(NewLayerResponse {
                 layer_id: Some(layer.widget_id.into_inner()),
@@ -206,9 +290,338 @@ impl layer_service_server::LayerService for LayerService {
         };
 
         run_unary_no_response(&self.sender, move |state| {
-            state
+            let id = WidgetId::from(id);
+            state.layer_groups.retain(|group| group.id != id);
+            state.close_layers(CloseReason::Request, |sn_layer| {
+                sn_layer.widget_id == id || sn_layer.group == Some(id)
+            });
+        })
+        .await
+    }
+
+    async fn hide(&self, request: Request<HideRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.layer_id else {
+            return Err(Status::invalid_argument("layer id was null"));
+        };
+
+        run_unary(&self.sender, move |state| {
+            let widget_id = WidgetId::from(id);
+
+            let is_group = match widget_id.group_for_mut(state) {
+                Some(group) => {
+                    group.template.hidden = true;
+                    true
+                }
+                None => false,
+            };
+
+            let mut found = false;
+
+            for layer in widget_id.layers_for_mut(state) {
+                found = true;
+                layer.hide(HideReason::Request);
+            }
+
+            if !found && !is_group {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn show(&self, request: Request<ShowRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.layer_id else {
+            return Err(Status::invalid_argument("layer id was null"));
+        };
+
+        run_unary(&self.sender, move |state| {
+            let widget_id = WidgetId::from(id);
+
+            let is_group = match widget_id.group_for_mut(state) {
+                Some(group) => {
+                    group.template.hidden = false;
+                    true
+                }
+                None => false,
+            };
+
+            let mut found = false;
+
+            for layer in state.layers.iter_mut().filter(|sn_layer| {
+                sn_layer.widget_id == widget_id || sn_layer.group == Some(widget_id)
+            }) {
+                found = true;
+                layer.show(&mut state.wgpu, &state.queue_handle);
+            }
+
+            if !found && !is_group {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_layer(
+        &self,
+        request: Request<UpdateLayerRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.layer_id else {
+            return Err(Status::invalid_argument("layer id was null"));
+        };
+
+        let Some(widget_def) = request.widget_def else {
+            return Err(Status::invalid_argument("no widget def"));
+        };
+        if widget_def.widget.is_none() {
+            return Err(Status::invalid_argument("widget def was null"));
+        }
+
+        run_unary(&self.sender, move |state| {
+            let widget_id = WidgetId::from(id);
+
+            let is_group = match widget_id.group_for_mut(state) {
+                Some(group) => {
+                    group.template.widget_def = widget_def.clone();
+                    true
+                }
+                None => false,
+            };
+
+            let mut found = false;
+
+            for layer in widget_id.layers_for_mut(state) {
+                found = true;
+
+                let Some((f, states)) = widget_def_to_fn(widget_def.clone()) else {
+                    return Err(Status::invalid_argument("widget def was null"));
+                };
+
+                // The main loop will schedule a frame now that the queue isn't empty
+                layer
+                    .widgets
+                    .queue_message(SnowcapMessage::UpdateProgram(Box::new(
+                        SnowcapWidgetProgram {
+                            widgets: f,
+                            widget_state: states,
+                        },
+                    )));
+                layer.widget_def = widget_def.clone();
+            }
+
+            if !found && !is_group {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_layer_properties(
+        &self,
+        request: Request<SetLayerPropertiesRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.layer_id else {
+            return Err(Status::invalid_argument("layer id was null"));
+        };
+
+        let anchor = request
+            .anchor_flags
+            .map(wlr_layer::Anchor::from_bits_truncate)
+            .or_else(|| {
+                request
+                    .anchor
+                    .and_then(|anchor| layer::v0alpha1::Anchor::try_from(anchor).ok())
+                    .map(wlr_layer::Anchor::from_api)
+            });
+        let exclusive_zone = request.exclusive_zone.map(ExclusiveZone::from_api);
+        let keyboard_interactivity = request
+            .keyboard_interactivity
+            .and_then(|interactivity| {
+                layer::v0alpha1::KeyboardInteractivity::try_from(interactivity).ok()
+            })
+            .map(wlr_layer::KeyboardInteractivity::from_api);
+        let z_layer = request
+            .layer
+            .and_then(|layer| layer::v0alpha1::Layer::try_from(layer).ok())
+            .map(wlr_layer::Layer::from_api);
+        let input_region = request.input_region.map(InputRegion::from_api);
+
+        run_unary(&self.sender, move |state| {
+            let widget_id = WidgetId::from(id);
+
+            let is_group = match widget_id.group_for_mut(state) {
+                Some(group) => {
+                    let template = &mut group.template;
+                    template.width = request.width.unwrap_or(template.width);
+                    template.height = request.height.unwrap_or(template.height);
+                    template.anchor = anchor.unwrap_or(template.anchor);
+                    template.exclusive_zone = exclusive_zone.unwrap_or(template.exclusive_zone);
+                    template.keyboard_interactivity =
+                        keyboard_interactivity.unwrap_or(template.keyboard_interactivity);
+                    template.layer = z_layer.unwrap_or(template.layer);
+                    if let Some(input_region) = input_region.clone() {
+                        template.input_region = input_region;
+                    }
+                    true
+                }
+                None => false,
+            };
+
+            let layers = state
                 .layers
-                .retain(|sn_layer| sn_layer.widget_id.into_inner() != id);
+                .iter_mut()
+                .filter(|sn_layer| {
+                    sn_layer.widget_id == widget_id || sn_layer.group == Some(widget_id)
+                })
+                .collect::<Vec<_>>();
+
+            if layers.is_empty() && !is_group {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            if z_layer.is_some() {
+                // `set_layer` was added in version 2 of the layer shell.
+                // `SurfaceKind` is non-exhaustive; assume other kinds can't do it.
+                let supported = layers.iter().all(|layer| match layer.layer.kind() {
+                    SurfaceKind::Wlr(wlr_surface) => wlr_surface.version() >= 2,
+                    _ => false,
+                });
+                if !supported {
+                    return Err(Status::failed_precondition(
+                        "the compositor does not support changing a layer's z-layer",
+                    ));
+                }
+            }
+
+            for layer in layers {
+                if let Some(z_layer) = z_layer {
+                    layer.layer.set_layer(z_layer);
+                    layer.z_layer = z_layer;
+                }
+                if let Some(anchor) = anchor {
+                    layer.layer.set_anchor(anchor);
+                    layer.anchor = anchor;
+                }
+                if let Some(exclusive_zone) = exclusive_zone {
+                    layer.layer.set_exclusive_zone(exclusive_zone.into());
+                    layer.exclusive_zone = exclusive_zone;
+                }
+                if let Some(keyboard_interactivity) = keyboard_interactivity {
+                    layer
+                        .layer
+                        .set_keyboard_interactivity(keyboard_interactivity);
+                    layer.keyboard_interactivity = keyboard_interactivity;
+                }
+                if let Some(input_region) = input_region.clone() {
+                    // Applied when the layer is updated below
+                    layer.input_region = input_region;
+                }
+
+                if let Some((max_width, max_height)) = layer.auto_size.as_mut() {
+                    // The layer is re-measured against the new maximum and anchor
+                    // when it's updated below
+                    *max_width = request.width.unwrap_or(*max_width);
+                    *max_height = request.height.unwrap_or(*max_height);
+                    layer.needs_measure = true;
+                } else if request.width.is_some() || request.height.is_some() {
+                    let width = request.width.unwrap_or(layer.width);
+                    let height = request.height.unwrap_or(layer.height);
+                    layer.set_size(width, height, &state.wgpu.device);
+                }
+
+                layer.layer.commit();
+
+                // Hidden layers are drawn with their new properties when they're shown
+                if !layer.visibility.is_hidden() {
+                    layer.update_and_draw(
+                        &state.wgpu.device,
+                        &state.wgpu.queue,
+                        &mut state.wgpu.renderer,
+                        &state.queue_handle,
+                    );
+                }
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn list_layers(
+        &self,
+        _request: Request<ListLayersRequest>,
+    ) -> Result<Response<ListLayersResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let layers = state.layers.iter().map(|layer| layer.to_api()).collect();
+
+            Ok(ListLayersResponse { layers })
+        })
+        .await
+    }
+
+    async fn get_layer(
+        &self,
+        request: Request<GetLayerRequest>,
+    ) -> Result<Response<GetLayerResponse>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.layer_id else {
+            return Err(Status::invalid_argument("layer id was null"));
+        };
+
+        run_unary(&self.sender, move |state| {
+            let Some(layer) = WidgetId::from(id).layer_for_mut(state) else {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            };
+
+            Ok(GetLayerResponse {
+                layer: Some(layer.to_api()),
+            })
+        })
+        .await
+    }
+
+    async fn watch_layer(
+        &self,
+        request: Request<WatchLayerRequest>,
+    ) -> Result<Response<Self::WatchLayerStream>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.layer_id else {
+            return Err(Status::invalid_argument("layer id was null"));
+        };
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            let widget_id = WidgetId::from(id);
+            let mut found = false;
+
+            if let Some(group) = widget_id.group_for_mut(state) {
+                group.layer_event_senders.push(sender.clone());
+                found = true;
+            }
+            for layer in widget_id.layers_for_mut(state) {
+                layer.layer_event_senders.push(sender.clone());
+                found = true;
+            }
+
+            if !found {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            Ok(())
         })
         .await
     }
@@ -4,7 +4,7 @@ use snowcap_api_defs::snowcap::input::v0alpha1::{
 };
 use tonic::{Request, Response, Status};
 
-use crate::widget::WidgetId;
+use crate::{layer::KeyboardKeySender, widget::WidgetId};
 
 use super::{run_server_streaming, ResponseStream, StateFnSender};
 
@@ -33,11 +33,29 @@ impl input_service_server::InputService for InputService {
             return Err(Status::invalid_argument("id was null"));
         };
 
+        let repeat = request.repeat();
+
         run_server_streaming(&self.sender, move |state, sender| {
-            if let Some(layer) = WidgetId::from(id).layer_for_mut(state) {
-                layer.keyboard_key_sender = Some(sender);
+            let widget_id = WidgetId::from(id);
+            let sender = KeyboardKeySender { sender, repeat };
+            let mut found = false;
+
+            if let Some(group) = widget_id.group_for_mut(state) {
+                group.keyboard_key_senders.push(sender.clone());
+                found = true;
+            }
+            for layer in widget_id.layers_for_mut(state) {
+                layer.keyboard_key_senders.push(sender.clone());
+                found = true;
             }
+
+            if !found {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            Ok(())
         })
+        .await
     }
 
     async fn pointer_button(
@@ -51,9 +69,24 @@ impl input_service_server::InputService for InputService {
         };
 
         run_server_streaming(&self.sender, move |state, sender| {
-            if let Some(layer) = WidgetId::from(id).layer_for_mut(state) {
-                layer.pointer_button_sender = Some(sender);
+            let widget_id = WidgetId::from(id);
+            let mut found = false;
+
+            if let Some(group) = widget_id.group_for_mut(state) {
+                group.pointer_button_sender = Some(sender.clone());
+                found = true;
+            }
+            for layer in widget_id.layers_for_mut(state) {
+                layer.pointer_button_sender = Some(sender.clone());
+                found = true;
             }
+
+            if !found {
+                return Err(Status::not_found(format!("layer {id} does not exist")));
+            }
+
+            Ok(())
         })
+        .await
     }
 }
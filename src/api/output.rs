@@ -0,0 +1,65 @@
+use snowcap_api_defs::snowcap::output::v0alpha1::{
+    output_service_server, watch_outputs_response, ListOutputsRequest, ListOutputsResponse,
+    WatchOutputsRequest, WatchOutputsResponse,
+};
+use tonic::{Request, Response, Status};
+
+use crate::{state::State, util::convert::ToApi};
+
+use super::{run_server_streaming, run_unary, ResponseStream, StateFnSender};
+
+pub struct OutputService {
+    sender: StateFnSender,
+}
+
+impl OutputService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+#[tonic::async_trait]
+impl output_service_server::OutputService for OutputService {
+    type WatchOutputsStream = ResponseStream<WatchOutputsResponse>;
+
+    async fn list_outputs(
+        &self,
+        _request: Request<ListOutputsRequest>,
+    ) -> Result<Response<ListOutputsResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let outputs = state
+                .output_state
+                .outputs()
+                .filter_map(|output| state.output_state.info(&output))
+                .map(|info| info.to_api())
+                .collect();
+
+            Ok(ListOutputsResponse { outputs })
+        })
+        .await
+    }
+
+    async fn watch_outputs(
+        &self,
+        _request: Request<WatchOutputsRequest>,
+    ) -> Result<Response<Self::WatchOutputsStream>, Status> {
+        run_server_streaming(&self.sender, |state, sender| {
+            state.output_event_senders.push(sender);
+            Ok(())
+        })
+        .await
+    }
+}
+
+impl State {
+    /// Send an event to all clients watching outputs.
+    pub fn send_output_event(&mut self, event: watch_outputs_response::Event) {
+        self.output_event_senders.retain(|sender| {
+            sender
+                .send(Ok(WatchOutputsResponse {
+                    event: Some(event.clone()),
+                }))
+                .is_ok()
+        });
+    }
+}
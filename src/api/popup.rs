@@ -0,0 +1,188 @@
+use smithay_client_toolkit::reexports::protocols::xdg::shell::client::xdg_positioner;
+use snowcap_api_defs::snowcap::popup::v0alpha1::{
+    popup_service_server, ClosePopupRequest, NewPopupRequest, NewPopupResponse, PopupCloseReason,
+    UpdatePopupRequest, WatchPopupRequest, WatchPopupResponse,
+};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    popup::{PopupPosition, SnowcapPopup},
+    util::convert::FromApi,
+    widget::{widget_def_to_fn, SnowcapMessage, SnowcapWidgetProgram, WidgetId},
+};
+
+use super::{
+    run_server_streaming, run_unary, run_unary_no_response, ResponseStream, StateFnSender,
+};
+
+pub struct PopupService {
+    sender: StateFnSender,
+}
+
+impl PopupService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+#[tonic::async_trait]
+impl popup_service_server::PopupService for PopupService {
+    type WatchPopupStream = ResponseStream<WatchPopupResponse>;
+
+    async fn new_popup(
+        &self,
+        request: Request<NewPopupRequest>,
+    ) -> Result<Response<NewPopupResponse>, Status> {
+        let request = request.into_inner();
+
+        let Some(parent_id) = request.parent_id else {
+            return Err(Status::invalid_argument("parent id was null"));
+        };
+
+        let anchor = xdg_positioner::Anchor::from_api(request.anchor());
+        let gravity = xdg_positioner::Gravity::from_api(request.gravity());
+        let constraint_adjustment = xdg_positioner::ConstraintAdjustment::from_bits_truncate(
+            request.constraint_adjustment(),
+        );
+        let offset = (request.offset_x(), request.offset_y());
+        let grab = request.grab();
+
+        let Some(widget_def) = request.widget_def else {
+            return Err(Status::invalid_argument("no widget def"));
+        };
+        if widget_def.widget.is_none() {
+            return Err(Status::invalid_argument("widget def was null"));
+        }
+
+        let width = request.width.unwrap_or(200);
+        let height = request.height.unwrap_or(200);
+        if width == 0 || height == 0 {
+            return Err(Status::invalid_argument("popup size must be non-zero"));
+        }
+
+        let anchor_rect = request.anchor_rect.unwrap_or_default();
+        let anchor_rect = (
+            anchor_rect.x(),
+            anchor_rect.y(),
+            anchor_rect.width.unwrap_or(1),
+            anchor_rect.height.unwrap_or(1),
+        );
+
+        let position = PopupPosition {
+            width,
+            height,
+            anchor_rect,
+            anchor,
+            gravity,
+            constraint_adjustment,
+            offset,
+        };
+
+        run_unary(&self.sender, move |state| {
+            let parent = WidgetId::from(parent_id);
+
+            let parent_exists = state.layers.iter().any(|layer| layer.widget_id == parent)
+                || state.popups.iter().any(|popup| popup.widget_id == parent);
+            if !parent_exists {
+                return Err(Status::not_found(format!(
+                    "layer or popup {parent_id} does not exist"
+                )));
+            }
+
+            let popup = SnowcapPopup::new(state, parent, position, grab, widget_def)
+                .map_err(|err| Status::internal(format!("{err:#}")))?;
+
+            let ret = Ok(NewPopupResponse {
+                popup_id: Some(popup.widget_id.into_inner()),
+            });
+
+            state.popups.push(popup);
+
+            ret
+        })
+        .await
+    }
+
+    async fn close(&self, request: Request<ClosePopupRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.popup_id else {
+            return Err(Status::invalid_argument("popup id was null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let id = WidgetId::from(id);
+            state.close_popups(PopupCloseReason::Request, |popup| popup.widget_id == id);
+        })
+        .await
+    }
+
+    async fn update_popup(
+        &self,
+        request: Request<UpdatePopupRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.popup_id else {
+            return Err(Status::invalid_argument("popup id was null"));
+        };
+
+        let Some(widget_def) = request.widget_def else {
+            return Err(Status::invalid_argument("no widget def"));
+        };
+
+        let Some((f, states)) = widget_def_to_fn(widget_def.clone()) else {
+            return Err(Status::invalid_argument("widget def was null"));
+        };
+
+        run_unary(&self.sender, move |state| {
+            let Some(popup) = state
+                .popups
+                .iter_mut()
+                .find(|popup| popup.widget_id == WidgetId::from(id))
+            else {
+                return Err(Status::not_found(format!("popup {id} does not exist")));
+            };
+
+            // The main loop will schedule a frame now that the queue isn't empty
+            popup
+                .widgets
+                .queue_message(SnowcapMessage::UpdateProgram(Box::new(
+                    SnowcapWidgetProgram {
+                        widgets: f,
+                        widget_state: states,
+                    },
+                )));
+            popup.widget_def = widget_def;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn watch_popup(
+        &self,
+        request: Request<WatchPopupRequest>,
+    ) -> Result<Response<Self::WatchPopupStream>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.popup_id else {
+            return Err(Status::invalid_argument("popup id was null"));
+        };
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            let Some(popup) = state
+                .popups
+                .iter_mut()
+                .find(|popup| popup.widget_id == WidgetId::from(id))
+            else {
+                return Err(Status::not_found(format!("popup {id} does not exist")));
+            };
+
+            popup.popup_event_senders.push(sender);
+
+            Ok(())
+        })
+        .await
+    }
+}
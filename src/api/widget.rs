@@ -0,0 +1,65 @@
+use snowcap_api_defs::snowcap::widget::v0alpha1::{
+    widget_service_server, WatchWidgetEventsRequest, WatchWidgetEventsResponse,
+};
+use tonic::{Request, Response, Status};
+
+use crate::widget::WidgetId;
+
+use super::{run_server_streaming, ResponseStream, StateFnSender};
+
+pub struct WidgetService {
+    sender: StateFnSender,
+}
+
+impl WidgetService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+#[tonic::async_trait]
+impl widget_service_server::WidgetService for WidgetService {
+    type WatchWidgetEventsStream = ResponseStream<WatchWidgetEventsResponse>;
+
+    async fn watch_widget_events(
+        &self,
+        request: Request<WatchWidgetEventsRequest>,
+    ) -> Result<Response<Self::WatchWidgetEventsStream>, Status> {
+        let request = request.into_inner();
+
+        let Some(id) = request.id else {
+            return Err(Status::invalid_argument("id was null"));
+        };
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            let widget_id = WidgetId::from(id);
+            let mut found = false;
+
+            if let Some(group) = widget_id.group_for_mut(state) {
+                group.widget_event_senders.push(sender.clone());
+                found = true;
+            }
+            for layer in widget_id.layers_for_mut(state) {
+                layer.widget_event_senders.push(sender.clone());
+                found = true;
+            }
+            if let Some(popup) = state
+                .popups
+                .iter_mut()
+                .find(|popup| popup.widget_id == widget_id)
+            {
+                popup.widget_event_senders.push(sender);
+                found = true;
+            }
+
+            if !found {
+                return Err(Status::not_found(format!(
+                    "layer or popup {id} does not exist"
+                )));
+            }
+
+            Ok(())
+        })
+        .await
+    }
+}
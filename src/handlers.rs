@@ -1,29 +1,47 @@
+pub mod fractional_scale;
 pub mod keyboard;
 pub mod pointer;
 
-use iced_wgpu::graphics::Viewport;
 use smithay_client_toolkit::{
     compositor::CompositorHandler,
     delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_seat,
+    delegate_xdg_popup,
+    globals::GlobalData,
     output::{OutputHandler, OutputState},
-    reexports::client::{
-        protocol::{
-            wl_output::{self, WlOutput},
-            wl_seat::WlSeat,
-            wl_surface::WlSurface,
+    reexports::{
+        client::{
+            delegate_dispatch,
+            protocol::{
+                wl_output::{self, WlOutput},
+                wl_seat::WlSeat,
+                wl_surface::WlSurface,
+            },
+            Connection, Dispatch, QueueHandle,
+        },
+        protocols::xdg::{
+            decoration::zv1::client::zxdg_decoration_manager_v1, shell::client::xdg_wm_base,
         },
-        Connection, QueueHandle,
     },
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
     seat::{Capability, SeatHandler, SeatState},
     shell::{
         wlr_layer::{LayerShellHandler, LayerSurface, LayerSurfaceConfigure},
+        xdg::{
+            popup::{Popup, PopupConfigure, PopupHandler},
+            XdgShell,
+        },
         WaylandSurface,
     },
 };
 
-use crate::state::State;
+use snowcap_api_defs::snowcap::{
+    layer::v0alpha1::{watch_layer_response, CloseReason},
+    output::v0alpha1::watch_outputs_response,
+    popup::v0alpha1::{watch_popup_response, PopupCloseReason},
+};
+
+use crate::{state::State, util::convert::ToApi};
 
 impl ProvidesRegistryState for State {
     fn registry(&mut self) -> &mut RegistryState {
@@ -51,7 +69,16 @@ impl SeatHandler for State {
         capability: Capability,
     ) {
         if capability == Capability::Keyboard && self.keyboard.is_none() {
-            let keyboard = self.seat_state.get_keyboard(qh, &seat, None).unwrap();
+            let keyboard = self
+                .seat_state
+                .get_keyboard_with_repeat(
+                    qh,
+                    &seat,
+                    None,
+                    self.loop_handle.clone(),
+                    Box::new(|state, _keyboard, event| state.key_pressed(event, true)),
+                )
+                .unwrap();
             self.keyboard = Some(keyboard);
         }
 
@@ -92,23 +119,45 @@ impl OutputHandler for State {
         &mut self.output_state
     }
 
-    fn new_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {
-        // TODO:
+    fn new_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, output: WlOutput) {
+        if let Some(info) = self.output_state.info(&output) {
+            self.send_output_event(watch_outputs_response::Event::Added(info.to_api()));
+        }
+
+        self.sync_layer_groups(&output);
     }
 
-    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {
-        // TODO:
+    fn update_output(&mut self, _conn: &Connection, qh: &QueueHandle<Self>, output: WlOutput) {
+        if let Some(info) = self.output_state.info(&output) {
+            self.send_output_event(watch_outputs_response::Event::Changed(info.to_api()));
+        }
+
+        self.update_layer_output_scales(qh, |sn_layer| sn_layer.current_outputs.contains(&output));
+
+        // The output's name or description may have changed what filters it matches
+        self.sync_layer_groups(&output);
     }
 
-    fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {
-        // TODO:
+    fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, output: WlOutput) {
+        if let Some(info) = self.output_state.info(&output) {
+            self.send_output_event(watch_outputs_response::Event::Removed(info.to_api()));
+        }
+
+        self.close_layers(CloseReason::OutputRemoved, |sn_layer| {
+            sn_layer.group.is_some() && sn_layer.output.as_ref() == Some(&output)
+        });
+
+        // The compositor may not send a leave for outputs that are gone
+        for layer in self.layers.iter_mut() {
+            layer.current_outputs.retain(|o| o != &output);
+        }
     }
 }
 delegate_output!(State);
 
 impl LayerShellHandler for State {
     fn closed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, layer: &LayerSurface) {
-        self.layers.retain(|sn_layer| &sn_layer.layer != layer);
+        self.close_layers(CloseReason::Compositor, |sn_layer| &sn_layer.layer == layer);
     }
 
     fn configure(
@@ -116,12 +165,42 @@ impl LayerShellHandler for State {
         _conn: &Connection,
         qh: &QueueHandle<Self>,
         layer: &LayerSurface,
-        _configure: LayerSurfaceConfigure,
+        configure: LayerSurfaceConfigure,
         _serial: u32,
     ) {
         let layer = self.layers.iter_mut().find(|l| &l.layer == layer);
 
         if let Some(layer) = layer {
+            // A dimension of 0 means the compositor is leaving it up to us.
+            // Auto-sized layers keep the size that fits their widgets.
+            let (width, height) = configure.new_size;
+            let (auto_width, auto_height) = layer.auto_sized_axes();
+            let width = if width == 0 || auto_width {
+                layer.width
+            } else {
+                width
+            };
+            let height = if height == 0 || auto_height {
+                layer.height
+            } else {
+                height
+            };
+
+            if (width, height) != (layer.width, layer.height) {
+                layer.resize(width, height, &self.wgpu.device);
+                // Widgets may wrap differently across a newly stretched axis
+                layer.needs_measure = layer.auto_size.is_some();
+            }
+
+            layer.visibility.configure();
+
+            layer.send_layer_event(watch_layer_response::Event::Configured(
+                watch_layer_response::Configured {
+                    width: Some(width),
+                    height: Some(height),
+                },
+            ));
+
             layer.update_and_draw(
                 &self.wgpu.device,
                 &self.wgpu.queue,
@@ -133,6 +212,66 @@ impl LayerShellHandler for State {
 }
 delegate_layer!(State);
 
+impl PopupHandler for State {
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        popup: &Popup,
+        config: PopupConfigure,
+    ) {
+        let Some(sn_popup) = self
+            .popups
+            .iter_mut()
+            .find(|sn_popup| &sn_popup.popup == popup)
+        else {
+            return;
+        };
+
+        let width = config.width.max(1) as u32;
+        let height = config.height.max(1) as u32;
+
+        if (width, height) != (sn_popup.width, sn_popup.height) {
+            sn_popup.resize(width, height, &self.wgpu.device);
+        }
+
+        sn_popup.send_popup_event(watch_popup_response::Event::Configured(
+            watch_popup_response::Configured {
+                x: Some(config.position.0),
+                y: Some(config.position.1),
+                width: Some(width),
+                height: Some(height),
+            },
+        ));
+
+        sn_popup.update_and_draw(&self.wgpu.device, &self.wgpu.queue, &mut self.wgpu.renderer);
+    }
+
+    fn done(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, popup: &Popup) {
+        self.close_popups(PopupCloseReason::Dismissed, |sn_popup| {
+            &sn_popup.popup == popup
+        });
+    }
+}
+// `delegate_xdg_shell` also delegates toplevel decorations, which need a `WindowHandler`.
+// Snowcap only makes popups, so the globals `XdgShell::bind` binds are handled here instead.
+delegate_dispatch!(State: [xdg_wm_base::XdgWmBase: GlobalData] => XdgShell);
+
+impl Dispatch<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1, GlobalData> for State {
+    fn event(
+        _state: &mut Self,
+        _proxy: &zxdg_decoration_manager_v1::ZxdgDecorationManagerV1,
+        _event: zxdg_decoration_manager_v1::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        // No events
+    }
+}
+
+delegate_xdg_popup!(State);
+
 impl CompositorHandler for State {
     fn scale_factor_changed(
         &mut self,
@@ -141,25 +280,20 @@ impl CompositorHandler for State {
         surface: &WlSurface,
         new_factor: i32,
     ) {
-        if let Some(layer) = self
-            .layers
-            .iter_mut()
-            .find(|sn_layer| sn_layer.layer.wl_surface() == surface)
-        {
-            layer.viewport = Viewport::with_physical_size(
-                iced::Size::new(
-                    layer.width * new_factor as u32,
-                    layer.height * new_factor as u32,
-                ),
-                new_factor as f64,
-            );
-            layer.set_scale(new_factor, &self.wgpu.device);
-            layer.update_and_draw(
-                &self.wgpu.device,
-                &self.wgpu.queue,
-                &mut self.wgpu.renderer,
-                qh,
-            );
+        // Fractionally scaled surfaces get their scale from `wp_fractional_scale_v1` instead
+        if let Some(layer) = self.layers.iter_mut().find(|sn_layer| {
+            sn_layer.layer.wl_surface() == surface && !sn_layer.is_fractionally_scaled()
+        }) {
+            if new_factor as f64 != layer.scale {
+                layer.rescale(new_factor as f64, &mut self.wgpu, qh);
+            }
+        } else if let Some(popup) = self.popups.iter_mut().find(|sn_popup| {
+            sn_popup.popup.wl_surface() == surface && !sn_popup.is_fractionally_scaled()
+        }) {
+            if new_factor as f64 != popup.scale {
+                popup.set_scale(new_factor as f64, &self.wgpu.device);
+                popup.update_and_draw(&self.wgpu.device, &self.wgpu.queue, &mut self.wgpu.renderer);
+            }
         }
     }
 
@@ -167,10 +301,21 @@ impl CompositorHandler for State {
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _surface: &WlSurface,
-        _new_transform: wl_output::Transform,
+        surface: &WlSurface,
+        new_transform: wl_output::Transform,
     ) {
-        // TODO:
+        if let Some(layer) = self
+            .layers
+            .iter_mut()
+            .find(|sn_layer| sn_layer.layer.wl_surface() == surface)
+        {
+            layer.set_transform(new_transform);
+            layer.send_layer_event(watch_layer_response::Event::TransformChanged(
+                watch_layer_response::TransformChanged {
+                    transform: Some(new_transform.to_api() as i32),
+                },
+            ));
+        }
     }
 
     fn frame(
@@ -192,27 +337,67 @@ impl CompositorHandler for State {
                 &mut self.wgpu.renderer,
                 qh,
             );
+        } else if let Some(popup) = self
+            .popups
+            .iter_mut()
+            .find(|sn_popup| sn_popup.popup.wl_surface() == surface)
+        {
+            popup.update_and_draw(&self.wgpu.device, &self.wgpu.queue, &mut self.wgpu.renderer);
         }
     }
 
     fn surface_enter(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &WlSurface,
-        _output: &wl_output::WlOutput,
+        qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        output: &wl_output::WlOutput,
     ) {
-        // TODO:
+        let info = self.output_state.info(output);
+
+        if let Some(layer) = self
+            .layers
+            .iter_mut()
+            .find(|sn_layer| sn_layer.layer.wl_surface() == surface)
+        {
+            if !layer.current_outputs.contains(output) {
+                layer.current_outputs.push(output.clone());
+            }
+            layer.send_layer_event(watch_layer_response::Event::OutputEntered(
+                watch_layer_response::OutputEntered {
+                    output_id: info.as_ref().map(|info| info.id),
+                    output_name: info.and_then(|info| info.name),
+                },
+            ));
+        }
+
+        self.update_layer_output_scales(qh, |sn_layer| sn_layer.layer.wl_surface() == surface);
     }
 
     fn surface_leave(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &WlSurface,
-        _output: &wl_output::WlOutput,
+        qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        output: &wl_output::WlOutput,
     ) {
-        // TODO:
+        let info = self.output_state.info(output);
+
+        if let Some(layer) = self
+            .layers
+            .iter_mut()
+            .find(|sn_layer| sn_layer.layer.wl_surface() == surface)
+        {
+            layer.current_outputs.retain(|o| o != output);
+            layer.send_layer_event(watch_layer_response::Event::OutputLeft(
+                watch_layer_response::OutputLeft {
+                    output_id: info.as_ref().map(|info| info.id),
+                    output_name: info.and_then(|info| info.name),
+                },
+            ));
+        }
+
+        self.update_layer_output_scales(qh, |sn_layer| sn_layer.layer.wl_surface() == surface);
     }
 }
 delegate_compositor!(State);
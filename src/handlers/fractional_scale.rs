@@ -0,0 +1,97 @@
+use smithay_client_toolkit::reexports::{
+    client::{Connection, Dispatch, QueueHandle},
+    protocols::wp::{
+        fractional_scale::v1::client::{
+            wp_fractional_scale_manager_v1::{self, WpFractionalScaleManagerV1},
+            wp_fractional_scale_v1::{self, WpFractionalScaleV1},
+        },
+        viewporter::client::{
+            wp_viewport::{self, WpViewport},
+            wp_viewporter::{self, WpViewporter},
+        },
+    },
+};
+
+use crate::{state::State, widget::WidgetId};
+
+/// The denominator of scales sent in `wp_fractional_scale_v1.preferred_scale`.
+const FRACTIONAL_SCALE_DENOMINATOR: f64 = 120.0;
+
+impl Dispatch<WpFractionalScaleManagerV1, ()> for State {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpFractionalScaleManagerV1,
+        _event: wp_fractional_scale_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        // No events
+    }
+}
+
+impl Dispatch<WpFractionalScaleV1, WidgetId> for State {
+    fn event(
+        state: &mut Self,
+        _proxy: &WpFractionalScaleV1,
+        event: wp_fractional_scale_v1::Event,
+        widget_id: &WidgetId,
+        _conn: &Connection,
+        qhandle: &QueueHandle<Self>,
+    ) {
+        let wp_fractional_scale_v1::Event::PreferredScale { scale } = event else {
+            return;
+        };
+
+        let scale = scale as f64 / FRACTIONAL_SCALE_DENOMINATOR;
+
+        if let Some(layer) = state
+            .layers
+            .iter_mut()
+            .find(|sn_layer| &sn_layer.widget_id == widget_id)
+        {
+            if scale != layer.scale {
+                layer.rescale(scale, &mut state.wgpu, qhandle);
+            }
+        } else if let Some(popup) = state
+            .popups
+            .iter_mut()
+            .find(|sn_popup| &sn_popup.widget_id == widget_id)
+        {
+            if scale != popup.scale {
+                popup.set_scale(scale, &state.wgpu.device);
+                popup.update_and_draw(
+                    &state.wgpu.device,
+                    &state.wgpu.queue,
+                    &mut state.wgpu.renderer,
+                );
+            }
+        }
+    }
+}
+
+impl Dispatch<WpViewporter, ()> for State {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpViewporter,
+        _event: wp_viewporter::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        // No events
+    }
+}
+
+impl Dispatch<WpViewport, ()> for State {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpViewport,
+        _event: wp_viewport::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        // No events
+    }
+}
@@ -5,9 +5,12 @@ use smithay_client_toolkit::{
         Connection, QueueHandle,
     },
     seat::keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
-    shell::{wlr_layer::LayerSurface, WaylandSurface},
+    shell::{wlr_layer::LayerSurface, xdg::popup::Popup, WaylandSurface},
+};
+use snowcap_api_defs::snowcap::{
+    input::{self, v0alpha1::KeyboardKeyResponse},
+    layer::v0alpha1::watch_layer_response,
 };
-use snowcap_api_defs::snowcap::input::{self, v0alpha1::KeyboardKeyResponse};
 
 use crate::{input::keyboard::keysym_to_iced_key_and_loc, state::State};
 
@@ -22,12 +25,21 @@ impl KeyboardHandler for State {
         _raw: &[u32],
         _keysyms: &[Keysym],
     ) {
-        if let Some(layer) = self
-            .layers
+        if let Some(popup) = self
+            .popups
             .iter()
+            .find(|sn_popup| sn_popup.popup.wl_surface() == surface)
+        {
+            self.keyboard_focus = Some(KeyboardFocus::Popup(popup.popup.clone()));
+        } else if let Some(layer) = self
+            .layers
+            .iter_mut()
             .find(|sn_layer| sn_layer.layer.wl_surface() == surface)
         {
             self.keyboard_focus = Some(KeyboardFocus::Layer(layer.layer.clone()));
+            layer.send_layer_event(watch_layer_response::Event::FocusGained(
+                watch_layer_response::FocusGained {},
+            ));
         }
     }
 
@@ -39,10 +51,23 @@ impl KeyboardHandler for State {
         surface: &WlSurface,
         _serial: u32,
     ) {
-        if let Some(KeyboardFocus::Layer(layer)) = self.keyboard_focus.as_ref() {
-            if layer.wl_surface() == surface {
-                self.keyboard_focus = None;
-            }
+        let focus_surface = match self.keyboard_focus.as_ref() {
+            Some(KeyboardFocus::Layer(layer)) => Some(layer.wl_surface()),
+            Some(KeyboardFocus::Popup(popup)) => Some(popup.wl_surface()),
+            None => None,
+        };
+        if focus_surface == Some(surface) {
+            self.keyboard_focus = None;
+        }
+
+        if let Some(layer) = self
+            .layers
+            .iter_mut()
+            .find(|sn_layer| sn_layer.layer.wl_surface() == surface)
+        {
+            layer.send_layer_event(watch_layer_response::Event::FocusLost(
+                watch_layer_response::FocusLost {},
+            ));
         }
     }
 
@@ -51,55 +76,12 @@ impl KeyboardHandler for State {
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
         _keyboard: &WlKeyboard,
-        _serial: u32,
+        serial: u32,
         event: KeyEvent,
     ) {
-        let Some(KeyboardFocus::Layer(layer)) = self.keyboard_focus.as_ref() else {
-            return;
-        };
+        self.last_input_serial = Some(serial);
 
-        let Some(snowcap_layer) = self.layers.iter_mut().find(|sn_l| &sn_l.layer == layer) else {
-            return;
-        };
-
-        let (key, location) = keysym_to_iced_key_and_loc(event.keysym);
-
-        let mut modifiers = iced::keyboard::Modifiers::empty();
-        if self.keyboard_modifiers.ctrl {
-            modifiers |= iced::keyboard::Modifiers::CTRL;
-        }
-        if self.keyboard_modifiers.alt {
-            modifiers |= iced::keyboard::Modifiers::ALT;
-        }
-        if self.keyboard_modifiers.shift {
-            modifiers |= iced::keyboard::Modifiers::SHIFT;
-        }
-        if self.keyboard_modifiers.logo {
-            modifiers |= iced::keyboard::Modifiers::LOGO;
-        }
-
-        snowcap_layer.widgets.queue_event(iced::Event::Keyboard(
-            iced::keyboard::Event::KeyPressed {
-                key,
-                location,
-                modifiers,
-                text: None,
-            },
-        ));
-
-        if let Some(sender) = snowcap_layer.keyboard_key_sender.as_ref() {
-            let api_modifiers = input::v0alpha1::Modifiers {
-                shift: Some(self.keyboard_modifiers.shift),
-                ctrl: Some(self.keyboard_modifiers.ctrl),
-                alt: Some(self.keyboard_modifiers.alt),
-                super_: Some(self.keyboard_modifiers.logo),
-            };
-            let _ = sender.send(Ok(KeyboardKeyResponse {
-                key: Some(event.keysym.raw()),
-                modifiers: Some(api_modifiers),
-                pressed: Some(true),
-            }));
-        }
+        self.key_pressed(event, false);
     }
 
     fn release_key(
@@ -110,37 +92,42 @@ impl KeyboardHandler for State {
         _serial: u32,
         event: KeyEvent,
     ) {
-        let Some(KeyboardFocus::Layer(layer)) = self.keyboard_focus.as_ref() else {
-            return;
-        };
-
-        let Some(snowcap_layer) = self.layers.iter_mut().find(|sn_l| &sn_l.layer == layer) else {
-            return;
-        };
-
         let (key, location) = keysym_to_iced_key_and_loc(event.keysym);
 
-        let mut modifiers = iced::keyboard::Modifiers::empty();
-        if self.keyboard_modifiers.ctrl {
-            modifiers |= iced::keyboard::Modifiers::CTRL;
-        }
-        if self.keyboard_modifiers.alt {
-            modifiers |= iced::keyboard::Modifiers::ALT;
-        }
-        if self.keyboard_modifiers.shift {
-            modifiers |= iced::keyboard::Modifiers::SHIFT;
-        }
-        if self.keyboard_modifiers.logo {
-            modifiers |= iced::keyboard::Modifiers::LOGO;
+        let iced_event = iced::Event::Keyboard(iced::keyboard::Event::KeyReleased {
+            key,
+            location,
+            modifiers: to_iced_modifiers(&self.keyboard_modifiers),
+        });
+
+        let api_modifiers = self.api_modifiers();
+
+        match self.keyboard_focus.as_ref() {
+            Some(KeyboardFocus::Layer(layer)) => {
+                if let Some(sn_layer) = self.layers.iter_mut().find(|sn_l| &sn_l.layer == layer) {
+                    sn_layer.widgets.queue_event(iced_event);
+
+                    sn_layer.send_keyboard_key(KeyboardKeyResponse {
+                        key: Some(event.keysym.raw()),
+                        modifiers: Some(api_modifiers),
+                        pressed: Some(false),
+                        repeat: Some(false),
+                        text: None,
+                        keysym_name: Some(xkbcommon::xkb::keysym_get_name(event.keysym)),
+                    });
+                }
+            }
+            Some(KeyboardFocus::Popup(popup)) => {
+                if let Some(sn_popup) = self
+                    .popups
+                    .iter_mut()
+                    .find(|sn_popup| &sn_popup.popup == popup)
+                {
+                    sn_popup.widgets.queue_event(iced_event);
+                }
+            }
+            None => (),
         }
-
-        snowcap_layer.widgets.queue_event(iced::Event::Keyboard(
-            iced::keyboard::Event::KeyReleased {
-                key,
-                location,
-                modifiers,
-            },
-        ));
     }
 
     fn update_modifiers(
@@ -150,14 +137,119 @@ impl KeyboardHandler for State {
         _keyboard: &WlKeyboard,
         _serial: u32,
         modifiers: Modifiers,
-        _layout: u32,
+        layout: u32,
     ) {
         // TODO: per wl_keyboard
         self.keyboard_modifiers = modifiers;
+        self.keyboard_layout = layout;
+
+        // Widgets like text inputs track modifiers for shortcuts and selection
+        let iced_event = iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(
+            to_iced_modifiers(&modifiers),
+        ));
+
+        match self.keyboard_focus.as_ref() {
+            Some(KeyboardFocus::Layer(layer)) => {
+                if let Some(sn_layer) = self.layers.iter_mut().find(|sn_l| &sn_l.layer == layer) {
+                    sn_layer.widgets.queue_event(iced_event);
+                }
+            }
+            Some(KeyboardFocus::Popup(popup)) => {
+                if let Some(sn_popup) = self
+                    .popups
+                    .iter_mut()
+                    .find(|sn_popup| &sn_popup.popup == popup)
+                {
+                    sn_popup.widgets.queue_event(iced_event);
+                }
+            }
+            None => (),
+        }
     }
 }
 delegate_keyboard!(State);
 
+impl State {
+    fn api_modifiers(&self) -> input::v0alpha1::Modifiers {
+        input::v0alpha1::Modifiers {
+            shift: Some(self.keyboard_modifiers.shift),
+            ctrl: Some(self.keyboard_modifiers.ctrl),
+            alt: Some(self.keyboard_modifiers.alt),
+            super_: Some(self.keyboard_modifiers.logo),
+            caps_lock: Some(self.keyboard_modifiers.caps_lock),
+            num_lock: Some(self.keyboard_modifiers.num_lock),
+            layout_index: Some(self.keyboard_layout),
+        }
+    }
+
+    /// Send a key press, or a repeat of a held key, to the focused layer or popup.
+    pub fn key_pressed(&mut self, event: KeyEvent, repeat: bool) {
+        let api_modifiers = self.api_modifiers();
+
+        let (key, location) = keysym_to_iced_key_and_loc(event.keysym);
+
+        // sctk runs keysyms through xkb_compose, so this is the composed text
+        // once a compose sequence finishes and nothing while it's in progress.
+        // Keys that produce no text give an empty string.
+        let text = event.utf8.filter(|text| !text.is_empty());
+
+        let iced_event = iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+            key,
+            location,
+            modifiers: to_iced_modifiers(&self.keyboard_modifiers),
+            text: text.as_deref().map(Into::into),
+        });
+
+        let layer = match self.keyboard_focus.as_ref() {
+            Some(KeyboardFocus::Layer(layer)) => layer,
+            Some(KeyboardFocus::Popup(popup)) => {
+                if let Some(sn_popup) = self
+                    .popups
+                    .iter_mut()
+                    .find(|sn_popup| &sn_popup.popup == popup)
+                {
+                    sn_popup.widgets.queue_event(iced_event);
+                }
+                return;
+            }
+            None => return,
+        };
+
+        let Some(snowcap_layer) = self.layers.iter_mut().find(|sn_l| &sn_l.layer == layer) else {
+            return;
+        };
+
+        snowcap_layer.widgets.queue_event(iced_event);
+
+        snowcap_layer.send_keyboard_key(KeyboardKeyResponse {
+            key: Some(event.keysym.raw()),
+            modifiers: Some(api_modifiers),
+            pressed: Some(true),
+            repeat: Some(repeat),
+            text,
+            keysym_name: Some(xkbcommon::xkb::keysym_get_name(event.keysym)),
+        });
+    }
+}
+
+fn to_iced_modifiers(modifiers: &Modifiers) -> iced::keyboard::Modifiers {
+    let mut iced_modifiers = iced::keyboard::Modifiers::empty();
+    if modifiers.ctrl {
+        iced_modifiers |= iced::keyboard::Modifiers::CTRL;
+    }
+    if modifiers.alt {
+        iced_modifiers |= iced::keyboard::Modifiers::ALT;
+    }
+    if modifiers.shift {
+        iced_modifiers |= iced::keyboard::Modifiers::SHIFT;
+    }
+    if modifiers.logo {
+        iced_modifiers |= iced::keyboard::Modifiers::LOGO;
+    }
+    iced_modifiers
+}
+
 pub enum KeyboardFocus {
     Layer(LayerSurface),
+    Popup(Popup),
 }
@@ -20,6 +20,30 @@ impl PointerHandler for State {
         events: &[PointerEvent],
     ) {
         for event in events {
+            if let PointerEventKind::Press { serial, .. } = event.kind {
+                self.last_input_serial = Some(serial);
+            }
+
+            if let Some(popup) = self
+                .popups
+                .iter_mut()
+                .find(|sn_popup| sn_popup.popup.wl_surface() == &event.surface)
+            {
+                match event.kind {
+                    PointerEventKind::Enter { .. } | PointerEventKind::Motion { .. } => {
+                        popup.pointer_location = Some(event.position);
+                    }
+                    PointerEventKind::Leave { .. } => popup.pointer_location = None,
+                    _ => (),
+                }
+
+                if let Some(iced_event) = pointer_event_to_iced(event) {
+                    popup.widgets.queue_event(iced_event);
+                }
+
+                continue;
+            }
+
             let Some(layer) = self
                 .layers
                 .iter_mut()
@@ -28,68 +52,95 @@ impl PointerHandler for State {
                 continue;
             };
 
-            let iced_event = match event.kind {
-                PointerEventKind::Enter { serial: _ } => {
+            match event.kind {
+                PointerEventKind::Enter { .. } => {
                     layer.pointer_location = Some(event.position);
-                    iced::Event::Mouse(iced::mouse::Event::CursorEntered)
+                    if let Some(timeout) = layer.timeout.as_mut() {
+                        if timeout.pause_on_hover {
+                            timeout.pause();
+                        }
+                    }
                 }
-                PointerEventKind::Leave { serial: _ } => {
+                PointerEventKind::Leave { .. } => {
                     layer.pointer_location = None;
-                    iced::Event::Mouse(iced::mouse::Event::CursorLeft)
+                    // A hidden layer's countdown restarts when it's shown
+                    if let Some(timeout) = layer
+                        .timeout
+                        .as_mut()
+                        .filter(|timeout| timeout.pause_on_hover && !layer.visibility.is_hidden())
+                    {
+                        timeout.resume();
+                    }
                 }
-                PointerEventKind::Motion { time: _ } => {
+                PointerEventKind::Motion { .. } => {
                     layer.pointer_location = Some(event.position);
-                    iced::Event::Mouse(iced::mouse::Event::CursorMoved {
-                        position: iced::Point {
-                            x: event.position.0 as f32,
-                            y: event.position.1 as f32,
-                        },
-                    })
-                }
-                PointerEventKind::Press {
-                    time: _,
-                    button,
-                    serial: _,
-                } => iced::Event::Mouse(iced::mouse::Event::ButtonPressed(button_to_iced_button(
-                    button,
-                ))),
-                PointerEventKind::Release {
-                    time: _,
-                    button,
-                    serial: _,
-                } => iced::Event::Mouse(iced::mouse::Event::ButtonReleased(button_to_iced_button(
-                    button,
-                ))),
-                PointerEventKind::Axis {
-                    time: _,
-                    horizontal,
-                    vertical,
-                    source,
-                } => {
-                    // Values are negated because they're backwards otherwise
-                    let delta = match source {
-                        Some(AxisSource::Wheel | AxisSource::WheelTilt) => ScrollDelta::Lines {
-                            x: -horizontal.discrete as f32,
-                            y: -vertical.discrete as f32,
-                        },
-                        Some(AxisSource::Finger | AxisSource::Continuous) => ScrollDelta::Pixels {
-                            x: -horizontal.absolute as f32,
-                            y: -vertical.absolute as f32,
-                        },
-                        // TODO: continue here or default to lines? prolly should
-                        // look at the protocol docs
-                        _ => continue,
-                    };
-                    iced::Event::Mouse(iced::mouse::Event::WheelScrolled { delta })
                 }
-            };
+                _ => (),
+            }
 
-            layer.widgets.queue_event(iced_event);
+            if let Some(iced_event) = pointer_event_to_iced(event) {
+                layer.widgets.queue_event(iced_event);
+            }
         }
     }
 }
 delegate_pointer!(State);
 
+fn pointer_event_to_iced(event: &PointerEvent) -> Option<iced::Event> {
+    let iced_event = match event.kind {
+        PointerEventKind::Enter { serial: _ } => {
+            iced::Event::Mouse(iced::mouse::Event::CursorEntered)
+        }
+        PointerEventKind::Leave { serial: _ } => iced::Event::Mouse(iced::mouse::Event::CursorLeft),
+        PointerEventKind::Motion { time: _ } => {
+            iced::Event::Mouse(iced::mouse::Event::CursorMoved {
+                position: iced::Point {
+                    x: event.position.0 as f32,
+                    y: event.position.1 as f32,
+                },
+            })
+        }
+        PointerEventKind::Press {
+            time: _,
+            button,
+            serial: _,
+        } => iced::Event::Mouse(iced::mouse::Event::ButtonPressed(button_to_iced_button(
+            button,
+        ))),
+        PointerEventKind::Release {
+            time: _,
+            button,
+            serial: _,
+        } => iced::Event::Mouse(iced::mouse::Event::ButtonReleased(button_to_iced_button(
+            button,
+        ))),
+        PointerEventKind::Axis {
+            time: _,
+            horizontal,
+            vertical,
+            source,
+        } => {
+            // Values are negated because they're backwards otherwise
+            let delta = match source {
+                Some(AxisSource::Wheel | AxisSource::WheelTilt) => ScrollDelta::Lines {
+                    x: -horizontal.discrete as f32,
+                    y: -vertical.discrete as f32,
+                },
+                Some(AxisSource::Finger | AxisSource::Continuous) => ScrollDelta::Pixels {
+                    x: -horizontal.absolute as f32,
+                    y: -vertical.absolute as f32,
+                },
+                // TODO: continue here or default to lines? prolly should
+                // look at the protocol docs
+                _ => return None,
+            };
+            iced::Event::Mouse(iced::mouse::Event::WheelScrolled { delta })
+        }
+    };
+
+    Some(iced_event)
+}
+
 fn button_to_iced_button(button: u32) -> iced::mouse::Button {
     match button {
         0x110 => iced::mouse::Button::Left,
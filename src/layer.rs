@@ -1,31 +1,62 @@
-use std::{num::NonZeroU32, ptr::NonNull};
+pub mod group;
+pub mod timeout;
+pub mod visibility;
+
+use std::{any::Any, collections::HashMap, num::NonZeroU32, ptr::NonNull};
+
+use anyhow::Context;
 
 use iced::{Color, Size, Theme};
 use iced_futures::Runtime;
-use iced_runtime::Debug;
-use iced_wgpu::{graphics::Viewport, wgpu::SurfaceTargetUnsafe};
+use iced_runtime::{Debug, Program};
+use iced_wgpu::{
+    core::{layout::Limits, widget::Tree, Layout, Rectangle},
+    graphics::Viewport,
+    wgpu::SurfaceTargetUnsafe,
+};
 use raw_window_handle::{
     RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
 };
 use smithay_client_toolkit::{
+    compositor::{CompositorState, Region},
+    output::{OutputData, OutputState},
     reexports::{
         calloop,
-        client::{Proxy, QueueHandle},
+        client::{
+            protocol::wl_output::{self, WlOutput},
+            Proxy, QueueHandle,
+        },
     },
     shell::{
         wlr_layer::{self, Anchor, LayerSurface},
         WaylandSurface,
     },
 };
-use snowcap_api_defs::snowcap::input::v0alpha1::{KeyboardKeyResponse, PointerButtonResponse};
+use snowcap_api_defs::snowcap::{
+    input::v0alpha1::{KeyboardKeyResponse, PointerButtonResponse},
+    layer::v0alpha1::{watch_layer_response, CloseReason, HideReason, WatchLayerResponse},
+    popup::v0alpha1::PopupCloseReason,
+    widget::v0alpha1::{WatchWidgetEventsResponse, WidgetDef},
+};
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::Status;
+use tracing::warn;
 
+use self::{
+    timeout::LayerTimeout,
+    visibility::{ShowAction, Visibility},
+};
 use crate::{
     clipboard::WaylandClipboard,
-    runtime::{CalloopSenderSink, CurrentTokioExecutor},
+    runtime::{spawn_command, CalloopSenderSink, CurrentTokioExecutor},
+    server::ClientInfo,
     state::State,
-    widget::{SnowcapMessage, SnowcapWidgetProgram, WidgetId},
+    util::scale::{physical_size, FractionalScale},
+    wgpu::Wgpu,
+    widget::{
+        interactive_bounds, send_widget_event, shrink_fill_lengths, widget_def_to_fn,
+        SnowcapMessage, SnowcapWidgetProgram, WidgetId, WidgetMessage,
+    },
 };
 
 pub struct SnowcapLayer {
@@ -35,8 +66,12 @@ pub struct SnowcapLayer {
 
     pub width: u32,
     pub height: u32,
-    pub scale: i32,
+    pub scale: f64,
     pub viewport: Viewport,
+    /// Set if the compositor supports fractional scaling.
+    fractional_scale: Option<FractionalScale>,
+    /// The buffer transform the compositor prefers, usually that of the layer's output.
+    pub transform: wl_output::Transform,
 
     pub widgets: iced_runtime::program::State<SnowcapWidgetProgram>,
     pub clipboard: WaylandClipboard,
@@ -46,9 +81,49 @@ pub struct SnowcapLayer {
     pub runtime: Runtime<CurrentTokioExecutor, CalloopSenderSink<SnowcapMessage>, SnowcapMessage>,
 
     pub widget_id: WidgetId,
-
-    pub keyboard_key_sender: Option<UnboundedSender<Result<KeyboardKeyResponse, Status>>>,
+    /// The output this layer was placed on, if one was requested.
+    pub output: Option<WlOutput>,
+    /// The outputs this layer is currently on, as told by `wl_surface.enter` and `leave`.
+    pub current_outputs: Vec<WlOutput>,
+    /// The layer group this layer is an instance of.
+    pub group: Option<WidgetId>,
+    /// The client that created this layer.
+    pub owner: Option<ClientInfo>,
+    /// Whether this layer should outlive its owner's connection.
+    pub keep_on_disconnect: bool,
+    /// If set, this layer is sized to fit its widgets, up to this maximum size.
+    pub auto_size: Option<(u32, u32)>,
+    /// Whether an auto-sized layer needs to be measured again
+    /// because its widgets or properties changed.
+    pub needs_measure: bool,
+    /// If set, this layer closes or hides itself when the countdown runs out.
+    pub timeout: Option<LayerTimeout>,
+    /// Whether this layer is hidden and whether its surface can be drawn to.
+    pub visibility: Visibility,
+    /// The part of this layer that accepts pointer input.
+    pub input_region: InputRegion,
+    /// The rectangles last set as the surface's input region,
+    /// or `None` if it's the whole surface.
+    applied_input_region: Option<Vec<(i32, i32, i32, i32)>>,
+    /// The bounds of interactive widgets from the last layout, cleared when
+    /// the layout may have changed.
+    automatic_input_region: Option<Vec<(i32, i32, i32, i32)>>,
+    /// Used to create regions.
+    compositor: CompositorState,
+
+    // The properties last requested for this layer, kept for introspection
+    pub namespace: String,
+    pub anchor: Anchor,
+    pub z_layer: wlr_layer::Layer,
+    pub exclusive_zone: ExclusiveZone,
+    pub keyboard_interactivity: wlr_layer::KeyboardInteractivity,
+    pub margin: Margin,
+    pub widget_def: WidgetDef,
+
+    pub keyboard_key_senders: Vec<KeyboardKeySender>,
     pub pointer_button_sender: Option<UnboundedSender<Result<PointerButtonResponse, Status>>>,
+    pub layer_event_senders: Vec<UnboundedSender<Result<WatchLayerResponse, Status>>>,
+    pub widget_event_senders: Vec<UnboundedSender<Result<WatchWidgetEventsResponse, Status>>>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -61,6 +136,79 @@ pub enum ExclusiveZone {
     Ignore,
 }
 
+/// A client watching a layer's keyboard input.
+#[derive(Debug, Clone)]
+pub struct KeyboardKeySender {
+    pub sender: UnboundedSender<Result<KeyboardKeyResponse, Status>>,
+    /// Whether the client wants repeated presses of held keys.
+    pub repeat: bool,
+}
+
+/// The distance a layer surface keeps from the edges it is anchored to.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Margin {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+/// The part of a layer surface that accepts pointer input.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum InputRegion {
+    /// The whole surface accepts input.
+    #[default]
+    Full,
+    /// Only these rectangles, as `(x, y, width, height)` in logical coordinates, accept input.
+    Rects(Vec<(i32, i32, i32, i32)>),
+    /// Only the bounds of widgets marked interactive accept input.
+    Automatic,
+}
+
+/// Which output a layer surface should be placed on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OutputSelector {
+    /// The output with the given name, like "DP-1".
+    Name(String),
+    /// The output with the given description.
+    Description(String),
+}
+
+impl OutputSelector {
+    /// Find the output this selector refers to.
+    pub fn resolve(&self, output_state: &OutputState) -> Option<WlOutput> {
+        output_state.outputs().find(|output| {
+            let Some(info) = output_state.info(output) else {
+                return false;
+            };
+
+            match self {
+                OutputSelector::Name(name) => info.name.as_ref() == Some(name),
+                OutputSelector::Description(desc) => info.description.as_ref() == Some(desc),
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for OutputSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputSelector::Name(name) => write!(f, "name `{name}`"),
+            OutputSelector::Description(desc) => write!(f, "description `{desc}`"),
+        }
+    }
+}
+
+impl From<ExclusiveZone> for i32 {
+    fn from(value: ExclusiveZone) -> Self {
+        match value {
+            ExclusiveZone::Exclusive(size) => size.get() as i32,
+            ExclusiveZone::Respect => 0,
+            ExclusiveZone::Ignore => -1,
+        }
+    }
+}
+
 impl SnowcapLayer {
     pub fn new(
         state: &mut State,
@@ -70,25 +218,59 @@ impl SnowcapLayer {
         anchor: Anchor,
         exclusive_zone: ExclusiveZone,
         keyboard_interactivity: wlr_layer::KeyboardInteractivity,
-        program: SnowcapWidgetProgram,
-    ) -> Self {
+        margin: Margin,
+        output: Option<WlOutput>,
+        namespace: String,
+        auto_size: bool,
+        widget_def: WidgetDef,
+    ) -> anyhow::Result<Self> {
+        let (widgets, widget_state) =
+            widget_def_to_fn(widget_def.clone()).context("widget def was null")?;
+        let program = SnowcapWidgetProgram {
+            widgets,
+            widget_state,
+        };
+
+        // When auto-sizing, the requested size is the most the layer can grow to.
+        // Until the compositor configures the layer, stretched axes are assumed
+        // to be that large.
+        let auto_size = auto_size.then_some((width, height));
+        let (width, height) = match auto_size {
+            Some(max_size) => fit_to_widgets(
+                &widget_def,
+                &program.widget_state,
+                &state.wgpu.renderer,
+                anchor,
+                max_size,
+                max_size,
+            ),
+            None => (width, height),
+        };
+        let (request_width, request_height) = match auto_size {
+            Some(_) => requested_size(anchor, width, height),
+            None => (width, height),
+        };
+
+        let next_id = state.widget_id_counter.next_and_increment();
+
         let surface = state.compositor_state.create_surface(&state.queue_handle);
+
+        let fractional_scale = FractionalScale::new(state, &surface, next_id);
+
+        let z_layer = layer;
         let layer = state.layer_shell_state.create_layer_surface(
             &state.queue_handle,
             surface,
-            layer,
-            Some("snowcap"),
-            None,
+            z_layer,
+            Some(namespace.as_str()),
+            output.as_ref(),
         );
 
-        layer.set_size(width, height);
+        layer.set_size(request_width, request_height);
         layer.set_anchor(anchor);
         layer.set_keyboard_interactivity(keyboard_interactivity);
-        layer.set_exclusive_zone(match exclusive_zone {
-            ExclusiveZone::Exclusive(size) => size.get() as i32,
-            ExclusiveZone::Respect => 0,
-            ExclusiveZone::Ignore => -1,
-        });
+        layer.set_exclusive_zone(exclusive_zone.into());
+        layer.set_margin(margin.top, margin.right, margin.bottom, margin.left);
 
         layer.commit();
 
@@ -107,22 +289,9 @@ impl SnowcapLayer {
                     raw_display_handle,
                     raw_window_handle,
                 })
-                .unwrap()
-        };
-
-        let surface_config = iced_wgpu::wgpu::SurfaceConfiguration {
-            usage: iced_wgpu::wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: iced_wgpu::wgpu::TextureFormat::Rgba8UnormSrgb,
-            width,
-            height,
-            present_mode: iced_wgpu::wgpu::PresentMode::Mailbox,
-            desired_maximum_frame_latency: 1,
-            alpha_mode: iced_wgpu::wgpu::CompositeAlphaMode::PreMultiplied,
-            view_formats: vec![iced_wgpu::wgpu::TextureFormat::Rgba8UnormSrgb],
+                .context("failed to create wgpu surface")?
         };
 
-        wgpu_surface.configure(&state.wgpu.device, &surface_config);
-
         let widgets = iced_runtime::program::State::new(
             program,
             [width as f32, height as f32].into(),
@@ -151,9 +320,18 @@ impl SnowcapLayer {
 
                     match message {
                         SnowcapMessage::Close => {
-                            state
-                                .layers
-                                .retain(|sn_layer| sn_layer.layer != layer_clone);
+                            state.close_layers(CloseReason::Request, |sn_layer| {
+                                sn_layer.layer == layer_clone
+                            });
+                        }
+                        SnowcapMessage::Widget(WidgetMessage::Event(widget_id, event)) => {
+                            let layer_id = layer.widget_id;
+                            send_widget_event(
+                                &mut layer.widget_event_senders,
+                                layer_id,
+                                widget_id,
+                                event,
+                            );
                         }
                         msg => {
                             layer.widgets.queue_message(msg);
@@ -178,23 +356,49 @@ impl SnowcapLayer {
         //     .into_recipes(),
         // );
 
-        let next_id = state.widget_id_counter.next_and_increment();
-
-        Self {
+        let mut sn_layer = Self {
             surface: wgpu_surface,
             layer,
             width,
             height,
-            scale: 1,
+            scale: 1.0,
             viewport: Viewport::with_physical_size(Size::new(width, height), 1.0),
+            fractional_scale,
+            transform: wl_output::Transform::Normal,
             widgets,
             clipboard,
             pointer_location: None,
             runtime,
             widget_id: next_id,
-            keyboard_key_sender: None,
+            output,
+            current_outputs: Vec::new(),
+            group: None,
+            owner: None,
+            keep_on_disconnect: false,
+            auto_size,
+            needs_measure: false,
+            timeout: None,
+            visibility: Visibility::default(),
+            input_region: InputRegion::Full,
+            applied_input_region: None,
+            automatic_input_region: None,
+            compositor: state.compositor_state.clone(),
+            namespace,
+            anchor,
+            z_layer,
+            exclusive_zone,
+            keyboard_interactivity,
+            margin,
+            widget_def,
+            keyboard_key_senders: Vec::new(),
             pointer_button_sender: None,
-        }
+            layer_event_senders: Vec::new(),
+            widget_event_senders: Vec::new(),
+        };
+
+        sn_layer.update_viewport(&state.wgpu.device);
+
+        Ok(sn_layer)
     }
 
     pub fn draw(
@@ -204,6 +408,10 @@ impl SnowcapLayer {
         renderer: &mut iced_wgpu::Renderer,
         _qh: &QueueHandle<State>,
     ) {
+        if self.width == 0 || self.height == 0 || self.visibility.is_hidden() {
+            return;
+        }
+
         let Ok(frame) = self.surface.get_current_texture() else {
             return;
         };
@@ -233,12 +441,10 @@ impl SnowcapLayer {
 
         queue.submit(Some(encoder.finish()));
 
-        self.layer.wl_surface().damage_buffer(
-            0,
-            0,
-            self.width as i32 * self.scale,
-            self.height as i32 * self.scale,
-        );
+        let (physical_width, physical_height) = self.physical_size();
+        self.layer
+            .wl_surface()
+            .damage_buffer(0, 0, physical_width as i32, physical_height as i32);
 
         // self.layer
         //     .wl_surface()
@@ -255,6 +461,90 @@ impl SnowcapLayer {
         renderer: &mut iced_wgpu::Renderer,
         qh: &QueueHandle<State>,
     ) {
+        let events = self.update_widgets(renderer);
+
+        if let Some(max_size) = self.auto_size.filter(|_| self.needs_measure) {
+            self.needs_measure = false;
+
+            let (width, height) = fit_to_widgets(
+                &self.widget_def,
+                &self.widgets.program().widget_state,
+                renderer,
+                self.anchor,
+                max_size,
+                (self.width, self.height),
+            );
+
+            if (width, height) != (self.width, self.height) {
+                self.set_size(width, height, device);
+                // Lay the widgets out again at the new size
+                self.update_widgets(renderer);
+            }
+        }
+
+        for event in events {
+            self.runtime.broadcast(event, iced::event::Status::Ignored);
+        }
+
+        self.update_input_region(renderer);
+
+        self.draw(device, queue, renderer, qh);
+    }
+
+    /// Set the surface's input region to match [`Self::input_region`] if it changed.
+    ///
+    /// This does not commit; the caller is responsible for that.
+    fn update_input_region(&mut self, renderer: &iced_wgpu::Renderer) {
+        let rects = match &self.input_region {
+            InputRegion::Full => None,
+            InputRegion::Rects(rects) => Some(rects.clone()),
+            InputRegion::Automatic => Some(
+                self.automatic_input_region
+                    .get_or_insert_with(|| {
+                        let element = self.widgets.program().view();
+                        let mut tree = Tree::new(&element);
+                        let limits = Limits::new(Size::ZERO, self.viewport.logical_size());
+                        let node = element.as_widget().layout(&mut tree, renderer, &limits);
+
+                        let mut bounds = Vec::new();
+                        interactive_bounds(&self.widget_def, Layout::new(&node), &mut bounds);
+
+                        bounds.into_iter().map(enclosing_rect).collect()
+                    })
+                    .clone(),
+            ),
+        };
+
+        if rects == self.applied_input_region {
+            return;
+        }
+
+        let surface = self.layer.wl_surface();
+
+        match rects.as_ref() {
+            Some(rects) => {
+                let region = match Region::new(&self.compositor) {
+                    Ok(region) => region,
+                    Err(err) => {
+                        warn!("Failed to create input region: {err}");
+                        return;
+                    }
+                };
+
+                for &(x, y, width, height) in rects {
+                    region.add(x, y, width, height);
+                }
+
+                // The region is copied, so it can be destroyed right away
+                surface.set_input_region(Some(region.wl_region()));
+            }
+            None => surface.set_input_region(None),
+        }
+
+        self.applied_input_region = rects;
+    }
+
+    fn update_widgets(&mut self, renderer: &mut iced_wgpu::Renderer) -> Vec<iced::Event> {
         let cursor = match self.pointer_location {
             Some((x, y)) => iced::mouse::Cursor::Available(iced::Point {
                 x: x as f32,
@@ -262,8 +552,7 @@ impl SnowcapLayer {
             }),
             None => iced::mouse::Cursor::Unavailable,
         };
-        // TODO: the command bit
-        let (events, _command) = self.widgets.update(
+        let (events, command) = self.widgets.update(
             self.viewport.logical_size(),
             cursor,
             renderer,
@@ -275,22 +564,205 @@ impl SnowcapLayer {
             &mut Debug::new(),
         );
 
-        for event in events {
-            self.runtime.broadcast(event, iced::event::Status::Ignored);
+        if let Some(command) = command {
+            // Messages were handled, so the widgets may have changed
+            self.needs_measure = self.auto_size.is_some();
+            self.automatic_input_region = None;
+
+            spawn_command(&mut self.runtime, command);
         }
 
-        self.draw(device, queue, renderer, qh);
+        events
     }
 
-    pub fn set_scale(&mut self, scale: i32, device: &iced_wgpu::wgpu::Device) {
+    /// Set the scale this layer is rendered at.
+    ///
+    /// Without fractional scaling support, `scale` should be a whole number.
+    pub fn set_scale(&mut self, scale: f64, device: &iced_wgpu::wgpu::Device) {
         self.scale = scale;
-        self.layer.wl_surface().set_buffer_scale(scale);
+
+        if self.fractional_scale.is_none() {
+            self.layer.wl_surface().set_buffer_scale(scale as i32);
+        }
+
+        self.update_viewport(device);
+    }
+
+    /// Change this layer's scale, notify watchers, and redraw at the new scale.
+    pub fn rescale(&mut self, scale: f64, wgpu: &mut Wgpu, qh: &QueueHandle<State>) {
+        self.set_scale(scale, &wgpu.device);
+        self.send_layer_event(watch_layer_response::Event::ScaleChanged(
+            watch_layer_response::ScaleChanged {
+                scale: Some(scale.ceil() as i32),
+                fractional_scale: Some(scale),
+            },
+        ));
+        self.update_and_draw(&wgpu.device, &wgpu.queue, &mut wgpu.renderer, qh);
+    }
+
+    /// The largest integer scale of the outputs this layer is on,
+    /// or `None` if it isn't on any.
+    pub fn output_scale(&self) -> Option<i32> {
+        self.current_outputs
+            .iter()
+            .filter_map(|output| output.data::<OutputData>())
+            .map(|data| data.scale_factor())
+            .max()
+    }
+
+    /// Record the buffer transform the compositor prefers for this layer.
+    ///
+    /// The transform is only recorded so it can be reported to clients.
+    /// iced can only render upright, so buffers are never pre-transformed and
+    /// the compositor rotates the layer when compositing it. Layer sizes are
+    /// surface-local, so they're already correct for the transform and
+    /// buffers are allocated without swapping their width and height.
+    pub fn set_transform(&mut self, transform: wl_output::Transform) {
+        self.transform = transform;
+    }
+
+    /// Whether this layer's scale comes from `wp_fractional_scale_v1`
+    /// rather than the integer scale of the outputs it's on.
+    pub fn is_fractionally_scaled(&self) -> bool {
+        self.fractional_scale.is_some()
+    }
+
+    /// Which of this layer's axes, as `(width, height)`, are sized to fit its widgets.
+    ///
+    /// Auto-sized layers leave the axes they're stretched across to the compositor.
+    pub fn auto_sized_axes(&self) -> (bool, bool) {
+        if self.auto_size.is_none() {
+            return (false, false);
+        }
+
+        let (stretched_width, stretched_height) = stretched_axes(self.anchor);
+        (!stretched_width, !stretched_height)
+    }
+
+    /// This layer's size in buffer pixels.
+    pub fn physical_size(&self) -> (u32, u32) {
+        physical_size(self.width, self.height, self.scale)
+    }
+
+    /// Request a new size for this layer and resize its buffers to match.
+    ///
+    /// This does not commit; the caller is responsible for that.
+    pub fn set_size(&mut self, width: u32, height: u32, device: &iced_wgpu::wgpu::Device) {
+        let (request_width, request_height) = match self.auto_size {
+            Some(_) => requested_size(self.anchor, width, height),
+            None => (width, height),
+        };
+        self.layer.set_size(request_width, request_height);
+        self.resize(width, height, device);
+    }
+
+    /// Resize this layer's wgpu surface and viewport to the given logical size.
+    pub fn resize(&mut self, width: u32, height: u32, device: &iced_wgpu::wgpu::Device) {
+        self.width = width;
+        self.height = height;
+        self.automatic_input_region = None;
+
+        self.update_viewport(device);
+    }
+
+    /// Resize the iced viewport and wgpu surface to match this layer's size and scale.
+    fn update_viewport(&mut self, device: &iced_wgpu::wgpu::Device) {
+        let (physical_width, physical_height) = self.physical_size();
+
+        self.viewport =
+            Viewport::with_physical_size(Size::new(physical_width, physical_height), self.scale);
+
+        if let Some(fractional_scale) = self.fractional_scale.as_ref() {
+            fractional_scale.set_destination(self.width, self.height);
+        }
+
+        self.configure_surface(device);
+    }
+
+    /// Unmap this layer, keeping its widgets and their state around.
+    pub fn hide(&mut self, reason: HideReason) {
+        if self.visibility.is_hidden() {
+            return;
+        }
+
+        if self.visibility.hide() {
+            let surface = self.layer.wl_surface();
+            surface.attach(None, 0, 0);
+            surface.commit();
+        }
+
+        if let Some(timeout) = self.timeout.as_mut() {
+            timeout.reset();
+        }
+
+        self.send_layer_event(watch_layer_response::Event::Hidden(
+            watch_layer_response::Hidden {
+                reason: Some(reason as i32),
+            },
+        ));
+    }
+
+    /// Map this layer again after it was hidden.
+    pub fn show(&mut self, wgpu: &mut Wgpu, qh: &QueueHandle<State>) {
+        let action = self.visibility.show();
+        if action == ShowAction::None {
+            return;
+        }
+
+        if let Some(timeout) = self.timeout.as_mut() {
+            timeout.resume();
+        }
+
+        match action {
+            ShowAction::None => (),
+            ShowAction::Draw => {
+                self.update_and_draw(&wgpu.device, &wgpu.queue, &mut wgpu.renderer, qh);
+            }
+            // Drawn once the compositor configures it
+            ShowAction::AwaitConfigure => self.layer.commit(),
+        }
+
+        self.send_layer_event(watch_layer_response::Event::Shown(
+            watch_layer_response::Shown {},
+        ));
+    }
+
+    /// Send a key event to all clients watching this layer's keyboard input.
+    pub fn send_keyboard_key(&mut self, response: KeyboardKeyResponse) {
+        self.keyboard_key_senders.retain(|sender| {
+            // Keep clients that didn't ask for repeats around without sending to them
+            if response.repeat() && !sender.repeat {
+                return !sender.sender.is_closed();
+            }
+            sender.sender.send(Ok(response.clone())).is_ok()
+        });
+    }
+
+    /// Send an event to all clients watching this layer.
+    pub fn send_layer_event(&mut self, event: watch_layer_response::Event) {
+        self.layer_event_senders.retain(|sender| {
+            sender
+                .send(Ok(WatchLayerResponse {
+                    event: Some(event.clone()),
+                }))
+                .is_ok()
+        });
+    }
+
+    fn configure_surface(&self, device: &iced_wgpu::wgpu::Device) {
+        // A dimension of 0 means the compositor will pick it for us
+        // in a configure, and wgpu can't configure a zero-sized surface.
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let (width, height) = self.physical_size();
 
         let surface_config = iced_wgpu::wgpu::SurfaceConfiguration {
             usage: iced_wgpu::wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: iced_wgpu::wgpu::TextureFormat::Rgba8UnormSrgb,
-            width: self.width * scale as u32,
-            height: self.height * scale as u32,
+            width,
+            height,
             present_mode: iced_wgpu::wgpu::PresentMode::Mailbox,
             desired_maximum_frame_latency: 2,
             alpha_mode: iced_wgpu::wgpu::CompositeAlphaMode::PreMultiplied,
@@ -300,3 +772,222 @@ impl SnowcapLayer {
         self.surface.configure(device, &surface_config);
     }
 }
+
+impl Drop for SnowcapLayer {
+    fn drop(&mut self) {
+        // This must be destroyed before the surface it belongs to
+        self.fractional_scale.take();
+    }
+}
+
+/// The smallest whole-pixel `(x, y, width, height)` rectangle containing `rect`.
+fn enclosing_rect(rect: Rectangle) -> (i32, i32, i32, i32) {
+    let x = rect.x.floor();
+    let y = rect.y.floor();
+    (
+        x as i32,
+        y as i32,
+        (rect.x + rect.width - x).ceil() as i32,
+        (rect.y + rect.height - y).ceil() as i32,
+    )
+}
+
+/// Whether a layer with the given anchor is stretched across its output's width and height.
+fn stretched_axes(anchor: Anchor) -> (bool, bool) {
+    (
+        anchor.contains(Anchor::LEFT | Anchor::RIGHT),
+        anchor.contains(Anchor::TOP | Anchor::BOTTOM),
+    )
+}
+
+/// The size an auto-sized layer should request from the compositor.
+///
+/// Axes the layer is stretched across are left for the compositor to size.
+fn requested_size(anchor: Anchor, width: u32, height: u32) -> (u32, u32) {
+    let (stretched_width, stretched_height) = stretched_axes(anchor);
+    (
+        if stretched_width { 0 } else { width },
+        if stretched_height { 0 } else { height },
+    )
+}
+
+/// The size an auto-sized layer should be to fit its widgets, up to `max_size`.
+///
+/// Axes the layer is stretched across are sized by the compositor,
+/// so they keep their `current_size` and aren't measured.
+fn fit_to_widgets(
+    widget_def: &WidgetDef,
+    widget_state: &HashMap<u32, Box<dyn Any + Send>>,
+    renderer: &iced_wgpu::Renderer,
+    anchor: Anchor,
+    max_size: (u32, u32),
+    current_size: (u32, u32),
+) -> (u32, u32) {
+    let (stretched_width, stretched_height) = stretched_axes(anchor);
+    let max_width = if stretched_width {
+        current_size.0
+    } else {
+        max_size.0
+    };
+    let max_height = if stretched_height {
+        current_size.1
+    } else {
+        max_size.1
+    };
+
+    let (width, height) =
+        measure_widgets(widget_def, widget_state, renderer, max_width, max_height);
+
+    (
+        if stretched_width {
+            current_size.0
+        } else {
+            width
+        },
+        if stretched_height {
+            current_size.1
+        } else {
+            height
+        },
+    )
+}
+
+/// Lay out widgets within a maximum size and return the size they take up.
+///
+/// Widgets that fill their space would always take up the maximum size,
+/// so they're measured shrunk to fit their contents instead.
+fn measure_widgets(
+    widget_def: &WidgetDef,
+    widget_state: &HashMap<u32, Box<dyn Any + Send>>,
+    renderer: &iced_wgpu::Renderer,
+    max_width: u32,
+    max_height: u32,
+) -> (u32, u32) {
+    let mut widget_def = widget_def.clone();
+    shrink_fill_lengths(&mut widget_def);
+
+    // Widget state ids only depend on the widget tree's structure,
+    // so the shrunk widgets can share the layer's state
+    let Some((widgets, _)) = widget_def_to_fn(widget_def) else {
+        return (max_width.max(1), max_height.max(1));
+    };
+    let element = widgets(widget_state);
+    let mut tree = Tree::new(&element);
+    let limits = Limits::new(Size::ZERO, Size::new(max_width as f32, max_height as f32));
+
+    let size = element
+        .as_widget()
+        .layout(&mut tree, renderer, &limits)
+        .size();
+
+    (
+        (size.width.ceil() as u32).min(max_width).max(1),
+        (size.height.ceil() as u32).min(max_height).max(1),
+    )
+}
+
+impl State {
+    /// Apply the scale of the outputs they're on to all layers matching the predicate.
+    ///
+    /// Compositors that send `wl_surface.preferred_buffer_scale` may not send it again
+    /// when an output's scale changes, so this keeps integer-scaled layers in sync.
+    /// Fractionally scaled layers are left alone.
+    pub fn update_layer_output_scales(
+        &mut self,
+        qh: &QueueHandle<State>,
+        mut predicate: impl FnMut(&SnowcapLayer) -> bool,
+    ) {
+        for layer in self.layers.iter_mut() {
+            if layer.is_fractionally_scaled() || !predicate(layer) {
+                continue;
+            }
+
+            let Some(scale) = layer.output_scale() else {
+                continue;
+            };
+
+            if scale as f64 != layer.scale {
+                layer.rescale(scale as f64, &mut self.wgpu, qh);
+            }
+        }
+    }
+
+    /// Close all layers matching the predicate, notifying their watchers of why.
+    pub fn close_layers(
+        &mut self,
+        reason: CloseReason,
+        mut predicate: impl FnMut(&SnowcapLayer) -> bool,
+    ) {
+        let to_close = self
+            .layers
+            .iter()
+            .filter(|sn_layer| predicate(sn_layer))
+            .map(|sn_layer| sn_layer.widget_id)
+            .collect::<Vec<_>>();
+
+        if to_close.is_empty() {
+            return;
+        }
+
+        // Popups need to be destroyed before their parent
+        self.close_popups(PopupCloseReason::ParentClosed, |sn_popup| {
+            to_close.contains(&sn_popup.parent)
+        });
+
+        self.layers.retain_mut(|sn_layer| {
+            if !to_close.contains(&sn_layer.widget_id) {
+                return true;
+            }
+
+            sn_layer.send_layer_event(watch_layer_response::Event::Closed(
+                watch_layer_response::Closed {
+                    reason: Some(reason as i32),
+                },
+            ));
+
+            false
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enclosing_rect_keeps_whole_pixel_bounds() {
+        let rect = Rectangle::new((10.0, 20.0).into(), Size::new(30.0, 40.0));
+
+        assert_eq!(enclosing_rect(rect), (10, 20, 30, 40));
+    }
+
+    #[test]
+    fn enclosing_rect_grows_to_cover_partial_pixels() {
+        let rect = Rectangle::new((10.5, 20.25).into(), Size::new(30.0, 40.5));
+
+        assert_eq!(enclosing_rect(rect), (10, 20, 31, 41));
+    }
+
+    #[test]
+    fn only_axes_between_opposite_anchors_are_stretched() {
+        assert_eq!(stretched_axes(Anchor::TOP), (false, false));
+        assert_eq!(
+            stretched_axes(Anchor::TOP | Anchor::LEFT | Anchor::RIGHT),
+            (true, false)
+        );
+        assert_eq!(stretched_axes(Anchor::all()), (true, true));
+    }
+
+    #[test]
+    fn stretched_axes_are_left_to_the_compositor() {
+        assert_eq!(requested_size(Anchor::BOTTOM, 200, 30), (200, 30));
+        assert_eq!(
+            requested_size(Anchor::BOTTOM | Anchor::LEFT | Anchor::RIGHT, 200, 30),
+            (0, 30)
+        );
+        assert_eq!(
+            requested_size(Anchor::LEFT | Anchor::TOP | Anchor::BOTTOM, 200, 30),
+            (200, 0)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::Context;
+use smithay_client_toolkit::{
+    output::OutputInfo,
+    reexports::client::protocol::wl_output::WlOutput,
+    shell::wlr_layer::{self, Anchor},
+};
+use snowcap_api_defs::snowcap::{
+    input::v0alpha1::PointerButtonResponse,
+    layer::v0alpha1::{CloseReason, WatchLayerResponse},
+    widget::v0alpha1::{WatchWidgetEventsResponse, WidgetDef},
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tonic::Status;
+use tracing::error;
+
+use crate::{server::ClientInfo, state::State, widget::WidgetId};
+
+use super::{
+    timeout::{LayerTimeout, TimeoutSettings},
+    ExclusiveZone, InputRegion, KeyboardKeySender, Margin, SnowcapLayer,
+};
+
+/// A layer that has one instance on every output matching a filter.
+pub struct LayerGroup {
+    pub id: WidgetId,
+    pub filter: OutputFilter,
+    pub template: LayerTemplate,
+
+    // Handed to every new instance so they behave like existing ones
+    pub keyboard_key_senders: Vec<KeyboardKeySender>,
+    pub pointer_button_sender: Option<UnboundedSender<Result<PointerButtonResponse, Status>>>,
+    pub layer_event_senders: Vec<UnboundedSender<Result<WatchLayerResponse, Status>>>,
+    pub widget_event_senders: Vec<UnboundedSender<Result<WatchWidgetEventsResponse, Status>>>,
+}
+
+/// The properties new instances of a layer group are created with.
+#[derive(Debug, Clone)]
+pub struct LayerTemplate {
+    pub width: u32,
+    pub height: u32,
+    pub layer: wlr_layer::Layer,
+    pub anchor: Anchor,
+    pub exclusive_zone: ExclusiveZone,
+    pub keyboard_interactivity: wlr_layer::KeyboardInteractivity,
+    pub margin: Margin,
+    pub namespace: String,
+    pub auto_size: bool,
+    pub input_region: InputRegion,
+    pub timeout: Option<TimeoutSettings>,
+    /// Whether new instances start out hidden.
+    pub hidden: bool,
+    pub widget_def: WidgetDef,
+    pub owner: Option<ClientInfo>,
+    pub keep_on_disconnect: bool,
+}
+
+/// Which outputs a layer group places instances on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OutputFilter {
+    /// Only match outputs with one of these names. If empty, any name matches.
+    pub names: Vec<String>,
+    /// Only match outputs whose description contains this string.
+    pub description_contains: Option<String>,
+}
+
+impl OutputFilter {
+    pub fn matches(&self, info: &OutputInfo) -> bool {
+        self.matches_name_and_description(info.name.as_deref(), info.description.as_deref())
+    }
+
+    fn matches_name_and_description(&self, name: Option<&str>, description: Option<&str>) -> bool {
+        let name_matches = self.names.is_empty()
+            || name.is_some_and(|name| self.names.iter().any(|wanted| wanted == name));
+
+        let description_matches = match &self.description_contains {
+            Some(needle) => description.is_some_and(|desc| desc.contains(needle.as_str())),
+            None => true,
+        };
+
+        name_matches && description_matches
+    }
+}
+
+impl LayerGroup {
+    pub fn new(id: WidgetId, filter: OutputFilter, template: LayerTemplate) -> Self {
+        Self {
+            id,
+            filter,
+            template,
+            keyboard_key_senders: Vec::new(),
+            pointer_button_sender: None,
+            layer_event_senders: Vec::new(),
+            widget_event_senders: Vec::new(),
+        }
+    }
+}
+
+impl State {
+    /// Create or remove instances of every layer group on the given output
+    /// so that only groups whose filter matches it have an instance there.
+    pub fn sync_layer_groups(&mut self, output: &WlOutput) {
+        let Some(info) = self.output_state.info(output) else {
+            return;
+        };
+
+        let mut to_spawn = Vec::new();
+        let mut to_remove = Vec::new();
+
+        for group in self.layer_groups.iter() {
+            let matches = group.filter.matches(&info);
+            let has_instance = self.layers.iter().any(|layer| {
+                layer.group == Some(group.id) && layer.output.as_ref() == Some(output)
+            });
+
+            if matches && !has_instance {
+                to_spawn.push(group.id);
+            } else if !matches && has_instance {
+                to_remove.push(group.id);
+            }
+        }
+
+        self.close_layers(CloseReason::OutputRemoved, |layer| {
+            layer.group.is_some_and(|group| to_remove.contains(&group))
+                && layer.output.as_ref() == Some(output)
+        });
+
+        for group_id in to_spawn {
+            if let Err(err) = self.spawn_group_instance(group_id, output.clone()) {
+                error!("Failed to create layer group instance: {err:#}");
+            }
+        }
+    }
+
+    /// Create an instance of the given layer group on an output.
+    pub fn spawn_group_instance(
+        &mut self,
+        group_id: WidgetId,
+        output: WlOutput,
+    ) -> anyhow::Result<()> {
+        let group = self
+            .layer_groups
+            .iter()
+            .find(|group| group.id == group_id)
+            .with_context(|| format!("layer group {} does not exist", group_id.into_inner()))?;
+
+        let template = group.template.clone();
+        let keyboard_key_senders = group.keyboard_key_senders.clone();
+        let pointer_button_sender = group.pointer_button_sender.clone();
+        let layer_event_senders = group.layer_event_senders.clone();
+        let widget_event_senders = group.widget_event_senders.clone();
+
+        let mut layer = SnowcapLayer::new(
+            self,
+            template.width,
+            template.height,
+            template.layer,
+            template.anchor,
+            template.exclusive_zone,
+            template.keyboard_interactivity,
+            template.margin,
+            Some(output),
+            template.namespace,
+            template.auto_size,
+            template.widget_def,
+        )?;
+
+        layer.group = Some(group_id);
+        layer.owner = template.owner;
+        layer.keep_on_disconnect = template.keep_on_disconnect;
+        layer.input_region = template.input_region;
+        layer.timeout = template
+            .timeout
+            .map(|settings| LayerTimeout::new(self.loop_handle.clone(), layer.widget_id, settings));
+        if template.hidden {
+            // The surface isn't configured yet, so this doesn't unmap anything
+            layer.visibility.hide();
+            if let Some(timeout) = layer.timeout.as_mut() {
+                timeout.reset();
+            }
+        }
+        layer.keyboard_key_senders = keyboard_key_senders;
+        layer.pointer_button_sender = pointer_button_sender;
+        layer.layer_event_senders = layer_event_senders;
+        layer.widget_event_senders = widget_event_senders;
+
+        self.layers.push(layer);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_filter_matches_every_output() {
+        let filter = OutputFilter::default();
+
+        assert!(filter.matches_name_and_description(Some("DP-1"), Some("Dell U2720Q")));
+        assert!(filter.matches_name_and_description(None, None));
+    }
+
+    #[test]
+    fn filter_matches_any_listed_name() {
+        let filter = OutputFilter {
+            names: vec!["DP-1".to_string(), "HDMI-A-1".to_string()],
+            description_contains: None,
+        };
+
+        assert!(filter.matches_name_and_description(Some("DP-1"), None));
+        assert!(filter.matches_name_and_description(Some("HDMI-A-1"), None));
+        assert!(!filter.matches_name_and_description(Some("eDP-1"), None));
+        assert!(!filter.matches_name_and_description(None, None));
+    }
+
+    #[test]
+    fn filter_matches_description_substrings() {
+        let filter = OutputFilter {
+            names: Vec::new(),
+            description_contains: Some("Dell".to_string()),
+        };
+
+        assert!(filter.matches_name_and_description(None, Some("Dell Inc. U2720Q")));
+        assert!(!filter.matches_name_and_description(None, Some("BOE 0x0BCA")));
+        assert!(!filter.matches_name_and_description(Some("DP-1"), None));
+    }
+
+    #[test]
+    fn filter_needs_both_name_and_description_to_match() {
+        let filter = OutputFilter {
+            names: vec!["DP-1".to_string()],
+            description_contains: Some("Dell".to_string()),
+        };
+
+        assert!(filter.matches_name_and_description(Some("DP-1"), Some("Dell Inc. U2720Q")));
+        assert!(!filter.matches_name_and_description(Some("DP-2"), Some("Dell Inc. U2720Q")));
+        assert!(!filter.matches_name_and_description(Some("DP-1"), Some("BOE 0x0BCA")));
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use smithay_client_toolkit::reexports::calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
+use snowcap_api_defs::snowcap::layer::v0alpha1::{CloseReason, HideReason};
+
+use crate::{state::State, widget::WidgetId};
+
+/// How a layer's timeout behaves.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutSettings {
+    pub duration: Duration,
+    /// Pause the countdown while the pointer is over the layer.
+    pub pause_on_hover: bool,
+    /// Hide the layer instead of closing it.
+    pub hide: bool,
+}
+
+/// A countdown that closes or hides a layer when it runs out.
+///
+/// The timer is removed from the event loop when this is dropped.
+pub struct LayerTimeout {
+    loop_handle: LoopHandle<'static, State>,
+    layer_id: WidgetId,
+    duration: Duration,
+    /// How much time is left as of `started`, or as of now if paused.
+    remaining: Duration,
+    /// When the countdown was last started or resumed. `None` while paused.
+    started: Option<Instant>,
+    token: Option<RegistrationToken>,
+    pub pause_on_hover: bool,
+    /// Hide the layer instead of closing it.
+    pub hide: bool,
+}
+
+impl LayerTimeout {
+    /// Start a countdown for the layer with the given id.
+    pub fn new(
+        loop_handle: LoopHandle<'static, State>,
+        layer_id: WidgetId,
+        settings: TimeoutSettings,
+    ) -> Self {
+        let mut timeout = Self {
+            loop_handle,
+            layer_id,
+            duration: settings.duration,
+            remaining: settings.duration,
+            started: None,
+            token: None,
+            pause_on_hover: settings.pause_on_hover,
+            hide: settings.hide,
+        };
+
+        timeout.resume();
+
+        timeout
+    }
+
+    /// Stop the countdown, keeping track of how much time is left.
+    pub fn pause(&mut self) {
+        let Some(started) = self.started.take() else {
+            return;
+        };
+
+        self.remaining = self.remaining.saturating_sub(started.elapsed());
+
+        if let Some(token) = self.token.take() {
+            self.loop_handle.remove(token);
+        }
+    }
+
+    /// Stop the countdown and rewind it to its full duration.
+    pub fn reset(&mut self) {
+        self.pause();
+        self.remaining = self.duration;
+    }
+
+    /// Continue a paused countdown.
+    pub fn resume(&mut self) {
+        if self.started.is_some() {
+            return;
+        }
+
+        let layer_id = self.layer_id;
+
+        let token = self
+            .loop_handle
+            .insert_source(Timer::from_duration(self.remaining), move |_, _, state| {
+                let Some(layer) = layer_id.layer_for_mut(state) else {
+                    return TimeoutAction::Drop;
+                };
+
+                let mut hide = false;
+                if let Some(timeout) = layer.timeout.as_mut() {
+                    // Returning `Drop` below removes this timer,
+                    // so don't have the layer's timeout remove it too
+                    timeout.token = None;
+                    timeout.started = None;
+                    hide = timeout.hide;
+                }
+
+                if hide {
+                    layer.hide(HideReason::TimedOut);
+                } else {
+                    state.close_layers(CloseReason::TimedOut, |layer| layer.widget_id == layer_id);
+                }
+
+                TimeoutAction::Drop
+            })
+            .expect("failed to insert layer timeout timer");
+
+        self.started = Some(Instant::now());
+        self.token = Some(token);
+    }
+}
+
+impl Drop for LayerTimeout {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.take() {
+            self.loop_handle.remove(token);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+/// Where a layer's surface is in layer-shell's configure/map cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SurfaceState {
+    /// The surface has been committed without a buffer and is waiting for a configure.
+    #[default]
+    Unconfigured,
+    /// The compositor configured the surface, so it can be drawn to.
+    Configured,
+    /// The surface was unmapped with a null buffer. It has to be committed
+    /// and configured again before it can be drawn to.
+    Unmapped,
+}
+
+/// Whether a layer is hidden, and what it takes to get it back on screen.
+///
+/// Layers are only unmapped if they could have been drawn. A group instance
+/// that starts out hidden is configured but never unmapped, so the compositor
+/// won't configure it again when it's shown and it has to be drawn right away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Visibility {
+    hidden: bool,
+    surface: SurfaceState,
+}
+
+/// What [`Visibility::show`] requires of the layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShowAction {
+    /// The layer wasn't hidden.
+    None,
+    /// The surface is configured; draw to it now.
+    Draw,
+    /// Commit the surface and draw once the compositor configures it.
+    AwaitConfigure,
+}
+
+impl Visibility {
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Record that the compositor configured the surface.
+    ///
+    /// Configures sent before the surface was unmapped are ignored.
+    pub fn configure(&mut self) {
+        if self.surface == SurfaceState::Unconfigured {
+            self.surface = SurfaceState::Configured;
+        }
+    }
+
+    /// Hide the layer.
+    ///
+    /// Returns whether the surface needs to be unmapped by attaching a null buffer.
+    pub fn hide(&mut self) -> bool {
+        if self.hidden {
+            return false;
+        }
+
+        self.hidden = true;
+
+        if self.surface == SurfaceState::Configured {
+            self.surface = SurfaceState::Unmapped;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Show the layer again.
+    pub fn show(&mut self) -> ShowAction {
+        if !self.hidden {
+            return ShowAction::None;
+        }
+
+        self.hidden = false;
+
+        match self.surface {
+            SurfaceState::Configured => ShowAction::Draw,
+            SurfaceState::Unconfigured | SurfaceState::Unmapped => {
+                // The commit the caller makes is the new initial commit
+                self.surface = SurfaceState::Unconfigured;
+                ShowAction::AwaitConfigure
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_before_configure_is_drawn_when_shown() {
+        let mut visibility = Visibility::default();
+
+        assert!(
+            !visibility.hide(),
+            "an unconfigured surface has nothing to unmap"
+        );
+        visibility.configure();
+
+        assert_eq!(visibility.show(), ShowAction::Draw);
+        assert!(!visibility.is_hidden());
+    }
+
+    #[test]
+    fn shown_before_first_configure_waits_for_it() {
+        let mut visibility = Visibility::default();
+
+        visibility.hide();
+        assert_eq!(visibility.show(), ShowAction::AwaitConfigure);
+
+        visibility.configure();
+        assert!(
+            visibility.hide(),
+            "the surface was drawn after its configure"
+        );
+    }
+
+    #[test]
+    fn unmapped_surface_waits_for_a_new_configure() {
+        let mut visibility = Visibility::default();
+        visibility.configure();
+
+        assert!(visibility.hide());
+        // A configure sent before the unmap doesn't count
+        visibility.configure();
+        assert_eq!(visibility.show(), ShowAction::AwaitConfigure);
+
+        visibility.configure();
+        assert!(visibility.hide());
+        visibility.configure();
+        assert_eq!(visibility.show(), ShowAction::AwaitConfigure);
+    }
+
+    #[test]
+    fn repeated_hide_and_show_are_no_ops() {
+        let mut visibility = Visibility::default();
+        visibility.configure();
+
+        assert_eq!(visibility.show(), ShowAction::None);
+        assert!(visibility.hide());
+        assert!(!visibility.hide());
+        assert!(visibility.is_hidden());
+    }
+}
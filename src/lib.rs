@@ -3,6 +3,7 @@ pub mod clipboard;
 pub mod handlers;
 pub mod input;
 pub mod layer;
+pub mod popup;
 pub mod runtime;
 pub mod server;
 pub mod state;
@@ -66,13 +67,17 @@ pub fn start(stop_signal_sender: Option<tokio::sync::oneshot::Sender<StopSignal>
                         handlers::keyboard::KeyboardFocus::Layer(layer) => {
                             !state.layers.iter().any(|sn_layer| &sn_layer.layer == layer)
                         }
+                        handlers::keyboard::KeyboardFocus::Popup(popup) => {
+                            !state.popups.iter().any(|sn_popup| &sn_popup.popup == popup)
+                        }
                     });
             if keyboard_focus_is_dead {
                 state.keyboard_focus = None;
             }
 
             for layer in state.layers.iter_mut() {
-                if !layer.widgets.is_queue_empty() {
+                // Hidden layers keep their queued messages until they're shown again
+                if !layer.visibility.is_hidden() && !layer.widgets.is_queue_empty() {
                     layer
                         .layer
                         .wl_surface()
@@ -80,6 +85,16 @@ pub fn start(stop_signal_sender: Option<tokio::sync::oneshot::Sender<StopSignal>
                     layer.layer.commit();
                 }
             }
+
+            for popup in state.popups.iter_mut() {
+                if !popup.widgets.is_queue_empty() {
+                    popup
+                        .popup
+                        .wl_surface()
+                        .frame(&state.queue_handle, popup.popup.wl_surface().clone());
+                    popup.popup.wl_surface().commit();
+                }
+            }
         })
         .unwrap();
 }
@@ -0,0 +1,470 @@
+use std::ptr::NonNull;
+
+use anyhow::Context;
+use iced::{Color, Size, Theme};
+use iced_futures::Runtime;
+use iced_runtime::Debug;
+use iced_wgpu::{graphics::Viewport, wgpu::SurfaceTargetUnsafe};
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
+};
+use smithay_client_toolkit::{
+    reexports::{calloop, client::Proxy, protocols::xdg::shell::client::xdg_positioner},
+    shell::xdg::{popup::Popup, XdgPositioner},
+};
+use snowcap_api_defs::snowcap::{
+    popup::v0alpha1::{watch_popup_response, PopupCloseReason, WatchPopupResponse},
+    widget::v0alpha1::{WatchWidgetEventsResponse, WidgetDef},
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tonic::Status;
+use tracing::warn;
+
+use crate::{
+    clipboard::WaylandClipboard,
+    runtime::{spawn_command, CalloopSenderSink, CurrentTokioExecutor},
+    state::State,
+    util::scale::{physical_size, FractionalScale},
+    widget::{
+        send_widget_event, widget_def_to_fn, SnowcapMessage, SnowcapWidgetProgram, WidgetId,
+        WidgetMessage,
+    },
+};
+
+/// An `xdg_popup` attached to a layer or another popup, showing its own widgets.
+pub struct SnowcapPopup {
+    // SAFETY: Drop order: surface needs to be dropped before the popup
+    surface: iced_wgpu::wgpu::Surface<'static>,
+    pub popup: Popup,
+
+    pub width: u32,
+    pub height: u32,
+    pub scale: f64,
+    pub viewport: Viewport,
+    /// Set if the compositor supports fractional scaling.
+    fractional_scale: Option<FractionalScale>,
+
+    pub widgets: iced_runtime::program::State<SnowcapWidgetProgram>,
+    pub clipboard: WaylandClipboard,
+
+    pub pointer_location: Option<(f64, f64)>,
+
+    pub runtime: Runtime<CurrentTokioExecutor, CalloopSenderSink<SnowcapMessage>, SnowcapMessage>,
+
+    pub widget_id: WidgetId,
+    /// The layer or popup this popup is attached to.
+    pub parent: WidgetId,
+    pub widget_def: WidgetDef,
+
+    pub popup_event_senders: Vec<UnboundedSender<Result<WatchPopupResponse, Status>>>,
+    pub widget_event_senders: Vec<UnboundedSender<Result<WatchWidgetEventsResponse, Status>>>,
+}
+
+/// Where a popup is placed relative to its parent. See `xdg_positioner`.
+#[derive(Debug, Clone, Copy)]
+pub struct PopupPosition {
+    pub width: u32,
+    pub height: u32,
+    /// x, y, width, and height of the anchor rectangle.
+    pub anchor_rect: (i32, i32, u32, u32),
+    pub anchor: xdg_positioner::Anchor,
+    pub gravity: xdg_positioner::Gravity,
+    pub constraint_adjustment: xdg_positioner::ConstraintAdjustment,
+    pub offset: (i32, i32),
+}
+
+impl SnowcapPopup {
+    pub fn new(
+        state: &mut State,
+        parent: WidgetId,
+        position: PopupPosition,
+        grab: bool,
+        widget_def: WidgetDef,
+    ) -> anyhow::Result<Self> {
+        let xdg_shell = state
+            .xdg_shell
+            .as_ref()
+            .context("the compositor does not support xdg-shell")?;
+
+        let (widgets, widget_state) =
+            widget_def_to_fn(widget_def.clone()).context("widget def was null")?;
+        let program = SnowcapWidgetProgram {
+            widgets,
+            widget_state,
+        };
+
+        let positioner = XdgPositioner::new(xdg_shell).context("failed to create positioner")?;
+        positioner.set_size(position.width as i32, position.height as i32);
+        let (x, y, width, height) = position.anchor_rect;
+        positioner.set_anchor_rect(x, y, width as i32, height as i32);
+        positioner.set_anchor(position.anchor);
+        positioner.set_gravity(position.gravity);
+        positioner.set_constraint_adjustment(position.constraint_adjustment);
+        positioner.set_offset(position.offset.0, position.offset.1);
+
+        let widget_id = state.widget_id_counter.next_and_increment();
+
+        let surface = state.compositor_state.create_surface(&state.queue_handle);
+
+        let fractional_scale = FractionalScale::new(state, &surface, widget_id);
+
+        // Popups start out at their parent's scale until the compositor says otherwise
+        let (popup, scale) = if let Some(layer) = state
+            .layers
+            .iter()
+            .find(|sn_layer| sn_layer.widget_id == parent)
+        {
+            let popup =
+                Popup::from_surface(None, &positioner, &state.queue_handle, surface, xdg_shell)
+                    .context("failed to create popup")?;
+            layer.layer.get_popup(popup.xdg_popup());
+            (popup, layer.scale)
+        } else if let Some(parent_popup) = state
+            .popups
+            .iter()
+            .find(|sn_popup| sn_popup.widget_id == parent)
+        {
+            let popup = Popup::from_surface(
+                Some(parent_popup.popup.xdg_surface()),
+                &positioner,
+                &state.queue_handle,
+                surface,
+                xdg_shell,
+            )
+            .context("failed to create popup")?;
+            (popup, parent_popup.scale)
+        } else {
+            anyhow::bail!("layer or popup {} does not exist", parent.into_inner());
+        };
+
+        if grab {
+            // Grabs must be in response to an input event
+            match (state.seat_state.seats().next(), state.last_input_serial) {
+                (Some(seat), Some(serial)) => popup.xdg_popup().grab(&seat, serial),
+                _ => warn!("Not grabbing the seat for popup: no recent input"),
+            }
+        }
+
+        match fractional_scale.as_ref() {
+            Some(fractional_scale) => {
+                fractional_scale.set_destination(position.width, position.height)
+            }
+            None => popup.wl_surface().set_buffer_scale(scale as i32),
+        }
+        popup.wl_surface().commit();
+
+        let raw_display_handle = RawDisplayHandle::Wayland(WaylandDisplayHandle::new(
+            NonNull::new(state.conn.backend().display_ptr() as *mut _).unwrap(),
+        ));
+        let raw_window_handle = RawWindowHandle::Wayland(WaylandWindowHandle::new(
+            NonNull::new(popup.wl_surface().id().as_ptr() as *mut _).unwrap(),
+        ));
+
+        let wgpu_surface = unsafe {
+            state
+                .wgpu
+                .instance
+                .create_surface_unsafe(SurfaceTargetUnsafe::RawHandle {
+                    raw_display_handle,
+                    raw_window_handle,
+                })
+                .context("failed to create wgpu surface")?
+        };
+
+        let widgets = iced_runtime::program::State::new(
+            program,
+            [position.width as f32, position.height as f32].into(),
+            &mut state.wgpu.renderer,
+            &mut iced_runtime::Debug::new(),
+        );
+
+        let clipboard =
+            unsafe { WaylandClipboard::new(state.conn.backend().display_ptr() as *mut _) };
+
+        let (sender, recv) = calloop::channel::channel::<SnowcapMessage>();
+        let runtime = Runtime::new(CurrentTokioExecutor, CalloopSenderSink::new(sender));
+
+        state
+            .loop_handle
+            .insert_source(recv, move |event, _, state| match event {
+                calloop::channel::Event::Msg(message) => {
+                    let Some(popup) = state
+                        .popups
+                        .iter_mut()
+                        .find(|sn_popup| sn_popup.widget_id == widget_id)
+                    else {
+                        return;
+                    };
+
+                    match message {
+                        SnowcapMessage::Close => {
+                            state.close_popups(PopupCloseReason::Request, |sn_popup| {
+                                sn_popup.widget_id == widget_id
+                            });
+                        }
+                        SnowcapMessage::Widget(WidgetMessage::Event(id, event)) => {
+                            send_widget_event(
+                                &mut popup.widget_event_senders,
+                                widget_id,
+                                id,
+                                event,
+                            );
+                        }
+                        msg => {
+                            popup.widgets.queue_message(msg);
+                        }
+                    }
+                }
+                calloop::channel::Event::Closed => (),
+            })
+            .unwrap();
+
+        let sn_popup = Self {
+            surface: wgpu_surface,
+            popup,
+            width: position.width,
+            height: position.height,
+            scale,
+            viewport: {
+                let (width, height) = physical_size(position.width, position.height, scale);
+                Viewport::with_physical_size(Size::new(width, height), scale)
+            },
+            fractional_scale,
+            widgets,
+            clipboard,
+            pointer_location: None,
+            runtime,
+            widget_id,
+            parent,
+            widget_def,
+            popup_event_senders: Vec::new(),
+            widget_event_senders: Vec::new(),
+        };
+
+        sn_popup.configure_surface(&state.wgpu.device);
+
+        Ok(sn_popup)
+    }
+
+    pub fn draw(
+        &self,
+        device: &iced_wgpu::wgpu::Device,
+        queue: &iced_wgpu::wgpu::Queue,
+        renderer: &mut iced_wgpu::Renderer,
+    ) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let Ok(frame) = self.surface.get_current_texture() else {
+            return;
+        };
+
+        let mut encoder =
+            device.create_command_encoder(&iced_wgpu::wgpu::CommandEncoderDescriptor::default());
+
+        let view = frame
+            .texture
+            .create_view(&iced_wgpu::wgpu::TextureViewDescriptor::default());
+
+        renderer.with_primitives(|backend, primitives| {
+            backend.present::<String>(
+                device,
+                queue,
+                &mut encoder,
+                Some(iced::Color::TRANSPARENT),
+                iced_wgpu::wgpu::TextureFormat::Rgba8UnormSrgb,
+                &view,
+                primitives,
+                &self.viewport,
+                &[],
+            );
+        });
+
+        queue.submit(Some(encoder.finish()));
+
+        let (physical_width, physical_height) = self.physical_size();
+        self.popup
+            .wl_surface()
+            .damage_buffer(0, 0, physical_width as i32, physical_height as i32);
+
+        // Does a commit
+        frame.present();
+    }
+
+    pub fn update_and_draw(
+        &mut self,
+        device: &iced_wgpu::wgpu::Device,
+        queue: &iced_wgpu::wgpu::Queue,
+        renderer: &mut iced_wgpu::Renderer,
+    ) {
+        let cursor = match self.pointer_location {
+            Some((x, y)) => iced::mouse::Cursor::Available(iced::Point {
+                x: x as f32,
+                y: y as f32,
+            }),
+            None => iced::mouse::Cursor::Unavailable,
+        };
+
+        let (events, command) = self.widgets.update(
+            self.viewport.logical_size(),
+            cursor,
+            renderer,
+            &Theme::CatppuccinFrappe,
+            &iced_wgpu::core::renderer::Style {
+                text_color: Color::WHITE,
+            },
+            &mut self.clipboard,
+            &mut Debug::new(),
+        );
+
+        if let Some(command) = command {
+            spawn_command(&mut self.runtime, command);
+        }
+
+        for event in events {
+            self.runtime.broadcast(event, iced::event::Status::Ignored);
+        }
+
+        self.draw(device, queue, renderer);
+    }
+
+    /// Set the scale this popup is rendered at.
+    ///
+    /// Without fractional scaling support, `scale` should be a whole number.
+    pub fn set_scale(&mut self, scale: f64, device: &iced_wgpu::wgpu::Device) {
+        self.scale = scale;
+
+        if self.fractional_scale.is_none() {
+            self.popup.wl_surface().set_buffer_scale(scale as i32);
+        }
+
+        self.resize(self.width, self.height, device);
+    }
+
+    /// Whether this popup's scale comes from `wp_fractional_scale_v1`
+    /// rather than the integer scale of the outputs it's on.
+    pub fn is_fractionally_scaled(&self) -> bool {
+        self.fractional_scale.is_some()
+    }
+
+    /// This popup's size in buffer pixels.
+    pub fn physical_size(&self) -> (u32, u32) {
+        physical_size(self.width, self.height, self.scale)
+    }
+
+    /// Resize this popup's wgpu surface and viewport to the given logical size.
+    pub fn resize(&mut self, width: u32, height: u32, device: &iced_wgpu::wgpu::Device) {
+        self.width = width;
+        self.height = height;
+
+        let (physical_width, physical_height) = self.physical_size();
+        self.viewport =
+            Viewport::with_physical_size(Size::new(physical_width, physical_height), self.scale);
+
+        if let Some(fractional_scale) = self.fractional_scale.as_ref() {
+            fractional_scale.set_destination(width, height);
+        }
+
+        self.configure_surface(device);
+    }
+
+    /// Send an event to all clients watching this popup.
+    pub fn send_popup_event(&mut self, event: watch_popup_response::Event) {
+        self.popup_event_senders.retain(|sender| {
+            sender
+                .send(Ok(WatchPopupResponse {
+                    event: Some(event.clone()),
+                }))
+                .is_ok()
+        });
+    }
+
+    fn configure_surface(&self, device: &iced_wgpu::wgpu::Device) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let (width, height) = self.physical_size();
+
+        let surface_config = iced_wgpu::wgpu::SurfaceConfiguration {
+            usage: iced_wgpu::wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: iced_wgpu::wgpu::TextureFormat::Rgba8UnormSrgb,
+            width,
+            height,
+            present_mode: iced_wgpu::wgpu::PresentMode::Mailbox,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: iced_wgpu::wgpu::CompositeAlphaMode::PreMultiplied,
+            view_formats: vec![iced_wgpu::wgpu::TextureFormat::Rgba8UnormSrgb],
+        };
+
+        self.surface.configure(device, &surface_config);
+    }
+}
+
+impl Drop for SnowcapPopup {
+    fn drop(&mut self) {
+        // This must be destroyed before the surface it belongs to
+        self.fractional_scale.take();
+    }
+}
+
+impl State {
+    /// Close all popups matching the predicate, along with any popups attached to them,
+    /// notifying their watchers of why.
+    pub fn close_popups(
+        &mut self,
+        reason: PopupCloseReason,
+        mut predicate: impl FnMut(&SnowcapPopup) -> bool,
+    ) {
+        let matched = self
+            .popups
+            .iter()
+            .filter(|sn_popup| predicate(sn_popup))
+            .map(|sn_popup| sn_popup.widget_id)
+            .collect::<Vec<_>>();
+
+        let mut to_close = matched.clone();
+
+        // Popups attached to a closed popup have to go too
+        loop {
+            let children = self
+                .popups
+                .iter()
+                .filter(|sn_popup| {
+                    to_close.contains(&sn_popup.parent) && !to_close.contains(&sn_popup.widget_id)
+                })
+                .map(|sn_popup| sn_popup.widget_id)
+                .collect::<Vec<_>>();
+
+            if children.is_empty() {
+                break;
+            }
+
+            to_close.extend(children);
+        }
+
+        // Children are always created after their parents, so closing from the back
+        // destroys the topmost popup first like xdg-shell requires
+        for i in (0..self.popups.len()).rev() {
+            let id = self.popups[i].widget_id;
+            if !to_close.contains(&id) {
+                continue;
+            }
+
+            let mut sn_popup = self.popups.remove(i);
+
+            let reason = if matched.contains(&id) {
+                reason
+            } else {
+                PopupCloseReason::ParentClosed
+            };
+
+            sn_popup.send_popup_event(watch_popup_response::Event::Closed(
+                watch_popup_response::Closed {
+                    reason: Some(reason as i32),
+                },
+            ));
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::{
     task::{Context, Poll},
 };
 
+use iced_futures::Runtime;
+use iced_runtime::{command::Action, Command};
 use smithay_client_toolkit::reexports::calloop;
 
 pub struct CurrentTokioExecutor;
@@ -58,3 +60,18 @@ impl<T> futures::Sink<T> for CalloopSenderSink<T> {
         Poll::Ready(Ok(()))
     }
 }
+
+/// Run the futures of a command returned by a widget program.
+///
+/// Their messages are sent through the runtime's sink back to the layer or popup.
+/// Other actions need a windowing shell and are ignored.
+pub fn spawn_command<M: Send + 'static>(
+    runtime: &mut Runtime<CurrentTokioExecutor, CalloopSenderSink<M>, M>,
+    command: Command<M>,
+) {
+    for action in command.actions() {
+        if let Action::Future(future) = action {
+            runtime.spawn(future);
+        }
+    }
+}
@@ -1,16 +1,34 @@
-use std::path::{Path, PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{Context as TaskContext, Poll},
+};
 
 use anyhow::Context;
 use smithay_client_toolkit::reexports::calloop;
 use snowcap_api_defs::snowcap::{
     input::v0alpha1::input_service_server::InputServiceServer,
-    layer::v0alpha1::layer_service_server::LayerServiceServer,
+    layer::v0alpha1::{layer_service_server::LayerServiceServer, CloseReason},
+    output::v0alpha1::output_service_server::OutputServiceServer,
+    popup::v0alpha1::popup_service_server::PopupServiceServer,
+    widget::v0alpha1::widget_service_server::WidgetServiceServer,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::UnixStream,
+    task::JoinHandle,
 };
-use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+use tonic::transport::server::Connected;
 use tracing::{error, info};
 
 use crate::{
-    api::{input::InputService, LayerService},
+    api::{
+        input::InputService, output::OutputService, popup::PopupService, widget::WidgetService,
+        LayerService, StateFnSender,
+    },
     state::State,
 };
 
@@ -25,6 +43,98 @@ fn socket_name() -> String {
     format!("snowcap-grpc-{wayland_suffix}.sock")
 }
 
+/// A unique identifier for a client connection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientId(u32);
+
+impl ClientId {
+    fn next() -> Self {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        Self(COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn into_inner(self) -> u32 {
+        self.0
+    }
+}
+
+/// Information about the client on the other end of a connection.
+///
+/// This is available as an extension on every request.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientInfo {
+    pub id: ClientId,
+    pub pid: Option<i32>,
+}
+
+/// A client connection that closes the client's layers when it's dropped.
+struct ClientStream {
+    inner: UnixStream,
+    info: ClientInfo,
+    sender: StateFnSender,
+}
+
+impl ClientStream {
+    fn new(inner: UnixStream, sender: StateFnSender) -> Self {
+        let pid = inner.peer_cred().ok().and_then(|cred| cred.pid());
+
+        Self {
+            inner,
+            info: ClientInfo {
+                id: ClientId::next(),
+                pid,
+            },
+            sender,
+        }
+    }
+}
+
+impl Drop for ClientStream {
+    fn drop(&mut self) {
+        let id = self.info.id;
+        // The event loop may already be gone if we're shutting down
+        let _ = self
+            .sender
+            .send(Box::new(move |state| state.client_disconnected(id)));
+    }
+}
+
+impl Connected for ClientStream {
+    type ConnectInfo = ClientInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.info
+    }
+}
+
+impl AsyncRead for ClientStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ClientStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
 pub struct GrpcServerState {
     _join_handle: JoinHandle<()>,
     socket_path: PathBuf,
@@ -37,6 +147,18 @@ impl Drop for GrpcServerState {
 }
 
 impl State {
+    /// Close the layers owned by a client that just disconnected,
+    /// unless they were created to outlive it.
+    pub fn client_disconnected(&mut self, id: ClientId) {
+        self.layer_groups.retain(|group| {
+            let template = &group.template;
+            template.keep_on_disconnect || template.owner.map(|owner| owner.id) != Some(id)
+        });
+        self.close_layers(CloseReason::ClientDisconnected, |layer| {
+            !layer.keep_on_disconnect && layer.owner.map(|owner| owner.id) == Some(id)
+        });
+    }
+
     pub fn start_grpc_server(&mut self, socket_dir: impl AsRef<Path>) -> anyhow::Result<()> {
         let socket_dir = socket_dir.as_ref();
         std::fs::create_dir_all(socket_dir)?;
@@ -61,18 +183,27 @@ impl State {
         // let snowcap_service = SnowcapService::new(grpc_sender.clone());
         let layer_service = LayerService::new(grpc_sender.clone());
         let input_service = InputService::new(grpc_sender.clone());
+        let output_service = OutputService::new(grpc_sender.clone());
+        let popup_service = PopupService::new(grpc_sender.clone());
+        let widget_service = WidgetService::new(grpc_sender.clone());
 
         let refl_service = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(snowcap_api_defs::FILE_DESCRIPTOR_SET)
             .build()?;
 
         let uds = tokio::net::UnixListener::bind(&socket_path)?;
-        let uds_stream = tokio_stream::wrappers::UnixListenerStream::new(uds);
+        let client_sender = grpc_sender.clone();
+        let uds_stream = tokio_stream::wrappers::UnixListenerStream::new(uds).map(move |stream| {
+            stream.map(|stream| ClientStream::new(stream, client_sender.clone()))
+        });
 
         let grpc_server = tonic::transport::Server::builder()
             .add_service(refl_service)
             .add_service(LayerServiceServer::new(layer_service))
-            .add_service(InputServiceServer::new(input_service));
+            .add_service(InputServiceServer::new(input_service))
+            .add_service(OutputServiceServer::new(output_service))
+            .add_service(PopupServiceServer::new(popup_service))
+            .add_service(WidgetServiceServer::new(widget_service));
 
         let join_handle = tokio::spawn(async move {
             if let Err(err) = grpc_server.serve_with_incoming(uds_stream).await {
@@ -10,15 +10,23 @@ use smithay_client_toolkit::{
             protocol::{wl_keyboard::WlKeyboard, wl_pointer::WlPointer},
             Connection, QueueHandle,
         },
+        protocols::wp::{
+            fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+            viewporter::client::wp_viewporter::WpViewporter,
+        },
     },
     registry::RegistryState,
     seat::{keyboard::Modifiers, SeatState},
-    shell::wlr_layer::LayerShell,
+    shell::{wlr_layer::LayerShell, xdg::XdgShell},
 };
+use snowcap_api_defs::snowcap::output::v0alpha1::WatchOutputsResponse;
+use tokio::sync::mpsc::UnboundedSender;
+use tonic::Status;
 
 use crate::{
     handlers::keyboard::KeyboardFocus,
-    layer::SnowcapLayer,
+    layer::{group::LayerGroup, SnowcapLayer},
+    popup::SnowcapPopup,
     server::GrpcServerState,
     wgpu::{setup_wgpu, Wgpu},
     widget::WidgetIdCounter,
@@ -34,6 +42,10 @@ pub struct State {
     pub output_state: OutputState,
     pub compositor_state: CompositorState,
     pub layer_shell_state: LayerShell,
+    /// Used to render at fractional scales. Only set if `viewporter` is also available.
+    pub fractional_scale_manager: Option<WpFractionalScaleManagerV1>,
+    pub viewporter: Option<WpViewporter>,
+    pub xdg_shell: Option<XdgShell>,
 
     pub grpc_server_state: Option<GrpcServerState>,
 
@@ -42,15 +54,24 @@ pub struct State {
     pub wgpu: Wgpu,
 
     pub layers: Vec<SnowcapLayer>,
+    pub layer_groups: Vec<LayerGroup>,
+    pub popups: Vec<SnowcapPopup>,
 
     // TODO: per wl_keyboard
     pub keyboard_focus: Option<KeyboardFocus>,
     pub keyboard_modifiers: Modifiers,
+    /// The index of the active keyboard layout.
+    pub keyboard_layout: u32,
     pub keyboard: Option<WlKeyboard>, // TODO: multiple
 
     pub pointer: Option<WlPointer>, // TODO: multiple
 
+    /// The serial of the last pointer button or key press, used to grab the seat for popups.
+    pub last_input_serial: Option<u32>,
+
     pub widget_id_counter: WidgetIdCounter,
+
+    pub output_event_senders: Vec<UnboundedSender<Result<WatchOutputsResponse, Status>>>,
 }
 
 impl State {
@@ -75,6 +96,20 @@ impl State {
 
         let compositor_state = CompositorState::bind(&globals, &queue_handle).unwrap();
 
+        // Only needed for popups, so don't fail without it
+        let xdg_shell = XdgShell::bind(&globals, &queue_handle).ok();
+
+        // Fractional scaling needs both protocols: one to learn the scale
+        // and one to tell the compositor the buffer's logical size
+        let viewporter = globals
+            .bind::<WpViewporter, _, _>(&queue_handle, 1..=1, ())
+            .ok();
+        let fractional_scale_manager = viewporter.as_ref().and_then(|_| {
+            globals
+                .bind::<WpFractionalScaleManagerV1, _, _>(&queue_handle, 1..=1, ())
+                .ok()
+        });
+
         WaylandSource::new(conn.clone(), event_queue)
             .insert(loop_handle.clone())
             .unwrap();
@@ -88,15 +123,23 @@ impl State {
             output_state,
             compositor_state,
             layer_shell_state,
+            fractional_scale_manager,
+            viewporter,
+            xdg_shell,
             grpc_server_state: None,
             queue_handle,
             wgpu: setup_wgpu()?,
             layers: Vec::new(),
+            layer_groups: Vec::new(),
+            popups: Vec::new(),
             keyboard_focus: None,
             keyboard_modifiers: smithay_client_toolkit::seat::keyboard::Modifiers::default(),
+            keyboard_layout: 0,
             keyboard: None,
             pointer: None,
+            last_input_serial: None,
             widget_id_counter: WidgetIdCounter::default(),
+            output_event_senders: Vec::new(),
         };
 
         Ok(state)
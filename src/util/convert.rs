@@ -1,12 +1,29 @@
 //! Utilities for converting to and from API types
 
-use snowcap_api_defs::snowcap::widget;
+use std::num::NonZeroU32;
+
+use smithay_client_toolkit::{
+    output::{OutputData, OutputInfo},
+    reexports::{
+        client::{protocol::wl_output, Proxy},
+        protocols::xdg::shell::client::xdg_positioner,
+    },
+    shell::wlr_layer,
+};
+use snowcap_api_defs::snowcap::{layer, output, popup, widget};
+
+use crate::layer::{group::OutputFilter, ExclusiveZone, InputRegion, Margin, SnowcapLayer};
 
 pub trait FromApi {
     type ApiType;
     fn from_api(api_type: Self::ApiType) -> Self;
 }
 
+pub trait ToApi {
+    type ApiType;
+    fn to_api(&self) -> Self::ApiType;
+}
+
 impl FromApi for iced::Length {
     type ApiType = widget::v0alpha1::Length;
 
@@ -203,3 +220,372 @@ impl FromApi for iced::Font {
         }
     }
 }
+
+impl FromApi for wlr_layer::Anchor {
+    type ApiType = layer::v0alpha1::Anchor;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type {
+            layer::v0alpha1::Anchor::Unspecified => wlr_layer::Anchor::empty(),
+            layer::v0alpha1::Anchor::Top => wlr_layer::Anchor::TOP,
+            layer::v0alpha1::Anchor::Bottom => wlr_layer::Anchor::BOTTOM,
+            layer::v0alpha1::Anchor::Left => wlr_layer::Anchor::LEFT,
+            layer::v0alpha1::Anchor::Right => wlr_layer::Anchor::RIGHT,
+            layer::v0alpha1::Anchor::TopLeft => wlr_layer::Anchor::TOP | wlr_layer::Anchor::LEFT,
+            layer::v0alpha1::Anchor::TopRight => wlr_layer::Anchor::TOP | wlr_layer::Anchor::RIGHT,
+            layer::v0alpha1::Anchor::BottomLeft => {
+                wlr_layer::Anchor::BOTTOM | wlr_layer::Anchor::LEFT
+            }
+            layer::v0alpha1::Anchor::BottomRight => {
+                wlr_layer::Anchor::BOTTOM | wlr_layer::Anchor::RIGHT
+            }
+        }
+    }
+}
+
+impl FromApi for ExclusiveZone {
+    type ApiType = i32;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type {
+            0 => ExclusiveZone::Respect,
+            x if x.is_positive() => ExclusiveZone::Exclusive(NonZeroU32::new(x as u32).unwrap()),
+            _ => ExclusiveZone::Ignore,
+        }
+    }
+}
+
+impl FromApi for Margin {
+    type ApiType = layer::v0alpha1::Margin;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        Margin {
+            top: api_type.top(),
+            right: api_type.right(),
+            bottom: api_type.bottom(),
+            left: api_type.left(),
+        }
+    }
+}
+
+impl FromApi for InputRegion {
+    type ApiType = layer::v0alpha1::InputRegion;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type.region {
+            Some(layer::v0alpha1::input_region::Region::Rects(rects)) => InputRegion::Rects(
+                rects
+                    .rects
+                    .into_iter()
+                    .map(|rect| {
+                        // Sizes too large for a region are as good as unbounded
+                        (
+                            rect.x(),
+                            rect.y(),
+                            i32::try_from(rect.width()).unwrap_or(i32::MAX),
+                            i32::try_from(rect.height()).unwrap_or(i32::MAX),
+                        )
+                    })
+                    .collect(),
+            ),
+            Some(layer::v0alpha1::input_region::Region::Automatic(())) => InputRegion::Automatic,
+            None => InputRegion::Full,
+        }
+    }
+}
+
+impl FromApi for OutputFilter {
+    type ApiType = layer::v0alpha1::OutputFilter;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        OutputFilter {
+            names: api_type.names,
+            description_contains: api_type.description_contains,
+        }
+    }
+}
+
+impl FromApi for wlr_layer::KeyboardInteractivity {
+    type ApiType = layer::v0alpha1::KeyboardInteractivity;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type {
+            layer::v0alpha1::KeyboardInteractivity::Unspecified
+            | layer::v0alpha1::KeyboardInteractivity::None => {
+                wlr_layer::KeyboardInteractivity::None
+            }
+            layer::v0alpha1::KeyboardInteractivity::OnDemand => {
+                wlr_layer::KeyboardInteractivity::OnDemand
+            }
+            layer::v0alpha1::KeyboardInteractivity::Exclusive => {
+                wlr_layer::KeyboardInteractivity::Exclusive
+            }
+        }
+    }
+}
+
+impl FromApi for wlr_layer::Layer {
+    type ApiType = layer::v0alpha1::Layer;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type {
+            layer::v0alpha1::Layer::Unspecified => wlr_layer::Layer::Top,
+            layer::v0alpha1::Layer::Background => wlr_layer::Layer::Background,
+            layer::v0alpha1::Layer::Bottom => wlr_layer::Layer::Bottom,
+            layer::v0alpha1::Layer::Top => wlr_layer::Layer::Top,
+            layer::v0alpha1::Layer::Overlay => wlr_layer::Layer::Overlay,
+        }
+    }
+}
+
+impl FromApi for xdg_positioner::Anchor {
+    type ApiType = popup::v0alpha1::Edge;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type {
+            popup::v0alpha1::Edge::Unspecified | popup::v0alpha1::Edge::None => {
+                xdg_positioner::Anchor::None
+            }
+            popup::v0alpha1::Edge::Top => xdg_positioner::Anchor::Top,
+            popup::v0alpha1::Edge::Bottom => xdg_positioner::Anchor::Bottom,
+            popup::v0alpha1::Edge::Left => xdg_positioner::Anchor::Left,
+            popup::v0alpha1::Edge::Right => xdg_positioner::Anchor::Right,
+            popup::v0alpha1::Edge::TopLeft => xdg_positioner::Anchor::TopLeft,
+            popup::v0alpha1::Edge::BottomLeft => xdg_positioner::Anchor::BottomLeft,
+            popup::v0alpha1::Edge::TopRight => xdg_positioner::Anchor::TopRight,
+            popup::v0alpha1::Edge::BottomRight => xdg_positioner::Anchor::BottomRight,
+        }
+    }
+}
+
+impl FromApi for xdg_positioner::Gravity {
+    type ApiType = popup::v0alpha1::Edge;
+
+    fn from_api(api_type: Self::ApiType) -> Self {
+        match api_type {
+            popup::v0alpha1::Edge::Unspecified | popup::v0alpha1::Edge::None => {
+                xdg_positioner::Gravity::None
+            }
+            popup::v0alpha1::Edge::Top => xdg_positioner::Gravity::Top,
+            popup::v0alpha1::Edge::Bottom => xdg_positioner::Gravity::Bottom,
+            popup::v0alpha1::Edge::Left => xdg_positioner::Gravity::Left,
+            popup::v0alpha1::Edge::Right => xdg_positioner::Gravity::Right,
+            popup::v0alpha1::Edge::TopLeft => xdg_positioner::Gravity::TopLeft,
+            popup::v0alpha1::Edge::BottomLeft => xdg_positioner::Gravity::BottomLeft,
+            popup::v0alpha1::Edge::TopRight => xdg_positioner::Gravity::TopRight,
+            popup::v0alpha1::Edge::BottomRight => xdg_positioner::Gravity::BottomRight,
+        }
+    }
+}
+
+impl ToApi for wl_output::Transform {
+    type ApiType = output::v0alpha1::Transform;
+
+    fn to_api(&self) -> Self::ApiType {
+        match self {
+            wl_output::Transform::Normal => output::v0alpha1::Transform::Normal,
+            wl_output::Transform::_90 => output::v0alpha1::Transform::Transform90,
+            wl_output::Transform::_180 => output::v0alpha1::Transform::Transform180,
+            wl_output::Transform::_270 => output::v0alpha1::Transform::Transform270,
+            wl_output::Transform::Flipped => output::v0alpha1::Transform::Flipped,
+            wl_output::Transform::Flipped90 => output::v0alpha1::Transform::Flipped90,
+            wl_output::Transform::Flipped180 => output::v0alpha1::Transform::Flipped180,
+            wl_output::Transform::Flipped270 => output::v0alpha1::Transform::Flipped270,
+            _ => output::v0alpha1::Transform::Unspecified,
+        }
+    }
+}
+
+impl ToApi for OutputInfo {
+    type ApiType = output::v0alpha1::OutputInfo;
+
+    fn to_api(&self) -> Self::ApiType {
+        let current_mode =
+            self.modes
+                .iter()
+                .find(|mode| mode.current)
+                .map(|mode| output::v0alpha1::Mode {
+                    width: Some(mode.dimensions.0),
+                    height: Some(mode.dimensions.1),
+                    refresh_rate: Some(mode.refresh_rate),
+                });
+
+        output::v0alpha1::OutputInfo {
+            id: Some(self.id),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            make: Some(self.make.clone()),
+            model: Some(self.model.clone()),
+            logical_x: self.logical_position.map(|(x, _)| x),
+            logical_y: self.logical_position.map(|(_, y)| y),
+            logical_width: self.logical_size.map(|(w, _)| w),
+            logical_height: self.logical_size.map(|(_, h)| h),
+            scale: Some(self.scale_factor),
+            transform: Some(self.transform.to_api() as i32),
+            current_mode,
+        }
+    }
+}
+
+impl ToApi for Margin {
+    type ApiType = layer::v0alpha1::Margin;
+
+    fn to_api(&self) -> Self::ApiType {
+        layer::v0alpha1::Margin {
+            top: Some(self.top),
+            right: Some(self.right),
+            bottom: Some(self.bottom),
+            left: Some(self.left),
+        }
+    }
+}
+
+impl ToApi for wlr_layer::KeyboardInteractivity {
+    type ApiType = layer::v0alpha1::KeyboardInteractivity;
+
+    fn to_api(&self) -> Self::ApiType {
+        match self {
+            wlr_layer::KeyboardInteractivity::None => layer::v0alpha1::KeyboardInteractivity::None,
+            wlr_layer::KeyboardInteractivity::Exclusive => {
+                layer::v0alpha1::KeyboardInteractivity::Exclusive
+            }
+            wlr_layer::KeyboardInteractivity::OnDemand => {
+                layer::v0alpha1::KeyboardInteractivity::OnDemand
+            }
+            _ => layer::v0alpha1::KeyboardInteractivity::Unspecified,
+        }
+    }
+}
+
+impl ToApi for wlr_layer::Layer {
+    type ApiType = layer::v0alpha1::Layer;
+
+    fn to_api(&self) -> Self::ApiType {
+        match self {
+            wlr_layer::Layer::Background => layer::v0alpha1::Layer::Background,
+            wlr_layer::Layer::Bottom => layer::v0alpha1::Layer::Bottom,
+            wlr_layer::Layer::Top => layer::v0alpha1::Layer::Top,
+            wlr_layer::Layer::Overlay => layer::v0alpha1::Layer::Overlay,
+            _ => layer::v0alpha1::Layer::Unspecified,
+        }
+    }
+}
+
+impl ToApi for SnowcapLayer {
+    type ApiType = layer::v0alpha1::LayerInfo;
+
+    fn to_api(&self) -> Self::ApiType {
+        layer::v0alpha1::LayerInfo {
+            layer_id: Some(self.widget_id.into_inner()),
+            namespace: Some(self.namespace.clone()),
+            width: Some(self.width),
+            height: Some(self.height),
+            scale: Some(self.scale.ceil() as i32),
+            fractional_scale: Some(self.scale),
+            anchor_flags: Some(self.anchor.bits()),
+            layer: Some(self.z_layer.to_api() as i32),
+            exclusive_zone: Some(self.exclusive_zone.into()),
+            keyboard_interactivity: Some(self.keyboard_interactivity.to_api() as i32),
+            margin: Some(self.margin.to_api()),
+            widget_def: Some(self.widget_def.clone()),
+            group_id: self.group.map(|group| group.into_inner()),
+            owner_client_id: self.owner.map(|owner| owner.id.into_inner()),
+            owner_pid: self.owner.and_then(|owner| owner.pid),
+            keep_on_disconnect: Some(self.keep_on_disconnect),
+            auto_size: Some(self.auto_size.is_some()),
+            hidden: Some(self.visibility.is_hidden()),
+            transform: Some(self.transform.to_api() as i32),
+            outputs: self
+                .current_outputs
+                .iter()
+                .filter_map(|output| output.data::<OutputData>())
+                .map(|data| data.with_output_info(|info| info.to_api()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive_zone_from_api() {
+        assert_eq!(
+            ExclusiveZone::from_api(32),
+            ExclusiveZone::Exclusive(NonZeroU32::new(32).unwrap())
+        );
+        assert_eq!(ExclusiveZone::from_api(0), ExclusiveZone::Respect);
+        assert_eq!(ExclusiveZone::from_api(-1), ExclusiveZone::Ignore);
+        assert_eq!(ExclusiveZone::from_api(i32::MIN), ExclusiveZone::Ignore);
+    }
+
+    #[test]
+    fn exclusive_zone_round_trips() {
+        for zone in [32, 0, -1] {
+            assert_eq!(i32::from(ExclusiveZone::from_api(zone)), zone);
+        }
+    }
+
+    #[test]
+    fn output_filter_from_api() {
+        let filter = OutputFilter::from_api(layer::v0alpha1::OutputFilter {
+            names: vec!["DP-1".to_string()],
+            description_contains: Some("Dell".to_string()),
+        });
+
+        assert_eq!(
+            filter,
+            OutputFilter {
+                names: vec!["DP-1".to_string()],
+                description_contains: Some("Dell".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn input_region_from_api() {
+        use layer::v0alpha1::input_region::{Rect, Rects, Region};
+
+        let rects = layer::v0alpha1::InputRegion {
+            region: Some(Region::Rects(Rects {
+                rects: vec![
+                    Rect {
+                        x: Some(-5),
+                        y: Some(10),
+                        width: Some(100),
+                        height: Some(20),
+                    },
+                    Rect {
+                        x: None,
+                        y: None,
+                        width: Some(8),
+                        height: None,
+                    },
+                    Rect {
+                        x: Some(0),
+                        y: Some(0),
+                        width: Some(u32::MAX),
+                        height: Some(i32::MAX as u32 + 1),
+                    },
+                ],
+            })),
+        };
+        assert_eq!(
+            InputRegion::from_api(rects),
+            InputRegion::Rects(vec![
+                (-5, 10, 100, 20),
+                (0, 0, 8, 0),
+                (0, 0, i32::MAX, i32::MAX)
+            ])
+        );
+
+        let automatic = layer::v0alpha1::InputRegion {
+            region: Some(Region::Automatic(())),
+        };
+        assert_eq!(InputRegion::from_api(automatic), InputRegion::Automatic);
+
+        let full = layer::v0alpha1::InputRegion { region: None };
+        assert_eq!(InputRegion::from_api(full), InputRegion::Full);
+    }
+}
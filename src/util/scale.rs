@@ -0,0 +1,92 @@
+use smithay_client_toolkit::reexports::{
+    client::protocol::wl_surface::WlSurface,
+    protocols::wp::{
+        fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1,
+        viewporter::client::wp_viewport::WpViewport,
+    },
+};
+
+use crate::{state::State, widget::WidgetId};
+
+/// Lets a surface render at a fractional scale.
+///
+/// The buffer is sized in physical pixels and scaled down with `wp_viewport`.
+/// Both objects are destroyed when this is dropped, which must happen
+/// before the surface they belong to is destroyed.
+pub struct FractionalScale {
+    fractional_scale: WpFractionalScaleV1,
+    viewport: WpViewport,
+}
+
+impl FractionalScale {
+    /// Start receiving fractional scales for the surface of the layer or popup
+    /// with the given id.
+    ///
+    /// Returns `None` if the compositor doesn't support fractional scaling.
+    pub fn new(state: &State, surface: &WlSurface, id: WidgetId) -> Option<Self> {
+        let (manager, viewporter) = state
+            .fractional_scale_manager
+            .as_ref()
+            .zip(state.viewporter.as_ref())?;
+
+        Some(Self {
+            fractional_scale: manager.get_fractional_scale(surface, &state.queue_handle, id),
+            viewport: viewporter.get_viewport(surface, &state.queue_handle, ()),
+        })
+    }
+
+    /// Set the logical size the buffer is scaled to.
+    pub fn set_destination(&self, width: u32, height: u32) {
+        // The destination can't be zero-sized; the compositor will configure a real size later
+        if width > 0 && height > 0 {
+            self.viewport.set_destination(width as i32, height as i32);
+        }
+    }
+}
+
+impl Drop for FractionalScale {
+    fn drop(&mut self) {
+        self.fractional_scale.destroy();
+        self.viewport.destroy();
+    }
+}
+
+/// The size in buffer pixels of a surface with the given logical size and scale.
+///
+/// Sizes are surface-local, like the ones in configure events, so they already
+/// account for the output's transform and are never swapped here.
+pub fn physical_size(width: u32, height: u32, scale: f64) -> (u32, u32) {
+    (
+        (width as f64 * scale).round() as u32,
+        (height as f64 * scale).round() as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_size_rounds_to_whole_pixels() {
+        assert_eq!(physical_size(100, 30, 1.0), (100, 30));
+        assert_eq!(physical_size(100, 30, 2.0), (200, 60));
+        assert_eq!(physical_size(101, 33, 1.25), (126, 41));
+        assert_eq!(physical_size(0, 30, 1.5), (0, 45));
+    }
+
+    #[test]
+    fn physical_size_is_not_swapped_for_rotated_outputs() {
+        // A bar anchored to the left of a 1920x1080 output rotated by 90 degrees
+        // is configured with its surface-local size, which is already portrait.
+        let (configured_width, configured_height) = (30, 1920);
+
+        assert_eq!(
+            physical_size(configured_width, configured_height, 1.0),
+            (30, 1920)
+        );
+        assert_eq!(
+            physical_size(configured_width, configured_height, 1.5),
+            (45, 2880)
+        );
+    }
+}
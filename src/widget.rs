@@ -1,17 +1,23 @@
 use std::{any::Any, collections::HashMap};
 
 use iced::{
-    widget::{Column, Container, Row, Scrollable},
+    widget::{button, Button, Column, Container, Row, Scrollable, TextInput},
     Command,
 };
 use iced_runtime::Program;
-use iced_wgpu::core::Element;
+use iced_wgpu::core::{Element, Layout, Rectangle};
 use snowcap_api_defs::snowcap::widget::{
     self,
-    v0alpha1::{widget_def, WidgetDef},
+    v0alpha1::{watch_widget_events_response, widget_def, WatchWidgetEventsResponse, WidgetDef},
 };
+use tokio::sync::mpsc::UnboundedSender;
+use tonic::Status;
 
-use crate::{layer::SnowcapLayer, state::State, util::convert::FromApi};
+use crate::{
+    layer::{group::LayerGroup, SnowcapLayer},
+    state::State,
+    util::convert::FromApi,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct WidgetId(u32);
@@ -38,6 +44,25 @@ impl WidgetId {
             .iter_mut()
             .find(|sn_layer| &sn_layer.widget_id == self)
     }
+
+    /// Get the layer with this id, or every instance of the layer group with this id.
+    pub fn layers_for_mut<'a>(
+        &self,
+        state: &'a mut State,
+    ) -> impl Iterator<Item = &'a mut SnowcapLayer> {
+        let id = *self;
+        state
+            .layers
+            .iter_mut()
+            .filter(move |sn_layer| sn_layer.widget_id == id || sn_layer.group == Some(id))
+    }
+
+    pub fn group_for_mut<'a>(&self, state: &'a mut State) -> Option<&'a mut LayerGroup> {
+        state
+            .layer_groups
+            .iter_mut()
+            .find(|group| &group.id == self)
+    }
 }
 
 impl From<u32> for WidgetId {
@@ -51,10 +76,20 @@ pub struct SnowcapWidgetProgram {
     pub widget_state: HashMap<u32, Box<dyn Any + Send>>,
 }
 
+impl std::fmt::Debug for SnowcapWidgetProgram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnowcapWidgetProgram")
+            .field("widgets", &"...")
+            .field("widget_state", &self.widget_state)
+            .finish()
+    }
+}
+
 pub type WidgetFn = Box<
     dyn for<'a> Fn(
-        &'a HashMap<u32, Box<dyn Any + Send>>,
-    ) -> Element<'a, SnowcapMessage, iced::Theme, iced_wgpu::Renderer>,
+            &'a HashMap<u32, Box<dyn Any + Send>>,
+        ) -> Element<'a, WidgetMessage, iced::Theme, iced_wgpu::Renderer>
+        + Send,
 >;
 
 #[derive(Debug)]
@@ -62,6 +97,94 @@ pub enum SnowcapMessage {
     Noop,
     Close,
     Update(u32, Box<dyn Any + Send>),
+    /// Replace the entire program with a new one, keeping the widget tree's cached state.
+    UpdateProgram(Box<SnowcapWidgetProgram>),
+    /// A message published by a widget.
+    Widget(WidgetMessage),
+}
+
+/// A message published by a widget.
+///
+/// iced needs these to be `Clone`, which [`SnowcapMessage`] can't be.
+#[derive(Debug, Clone)]
+pub enum WidgetMessage {
+    /// An event from the widget with the given client-assigned id.
+    ///
+    /// The program hands this back to its layer or popup, which sends it to clients.
+    Event(u32, WidgetEvent),
+    /// The text in the text input with the given state id changed.
+    TextInputChanged {
+        state_id: u32,
+        widget_id: Option<u32>,
+        text: String,
+    },
+}
+
+/// Something that happened to a widget with a client-assigned id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WidgetEvent {
+    ButtonPressed,
+    TextChanged(String),
+    TextSubmitted(String),
+}
+
+/// The state of a text input, kept in [`SnowcapWidgetProgram::widget_state`].
+#[derive(Debug)]
+pub struct TextInputState {
+    /// The value the client gave the input.
+    initial: Option<String>,
+    /// What is currently in the input.
+    value: String,
+}
+
+/// Send an event from the widget with the given client-assigned id
+/// in a layer or popup to all clients watching it.
+pub fn send_widget_event(
+    senders: &mut Vec<UnboundedSender<Result<WatchWidgetEventsResponse, Status>>>,
+    layer_id: WidgetId,
+    widget_id: u32,
+    event: WidgetEvent,
+) {
+    let event = match event {
+        WidgetEvent::ButtonPressed => watch_widget_events_response::Event::ButtonPressed(
+            watch_widget_events_response::ButtonPressed {},
+        ),
+        WidgetEvent::TextChanged(text) => watch_widget_events_response::Event::TextChanged(
+            watch_widget_events_response::TextChanged { text: Some(text) },
+        ),
+        WidgetEvent::TextSubmitted(text) => watch_widget_events_response::Event::TextSubmitted(
+            watch_widget_events_response::TextSubmitted { text: Some(text) },
+        ),
+    };
+
+    senders.retain(|sender| {
+        sender
+            .send(Ok(WatchWidgetEventsResponse {
+                layer_id: Some(layer_id.into_inner()),
+                widget_id: Some(widget_id),
+                event: Some(event.clone()),
+            }))
+            .is_ok()
+    });
+}
+
+/// Keep what was typed into text inputs when replacing widget state,
+/// unless the client changed their value.
+fn keep_text_input_values(
+    old_states: &HashMap<u32, Box<dyn Any + Send>>,
+    new_states: &mut HashMap<u32, Box<dyn Any + Send>>,
+) {
+    for (id, state) in new_states.iter_mut() {
+        let Some(new) = state.downcast_mut::<TextInputState>() else {
+            continue;
+        };
+        let old = old_states
+            .get(id)
+            .and_then(|old| old.downcast_ref::<TextInputState>());
+        if let Some(old) = old.filter(|old| old.initial == new.initial) {
+            new.value.clone_from(&old.value);
+        }
+    }
 }
 
 impl Program for SnowcapWidgetProgram {
@@ -78,12 +201,44 @@ impl Program for SnowcapWidgetProgram {
             SnowcapMessage::Update(id, data) => {
                 self.widget_state.insert(id, data);
             }
+            SnowcapMessage::UpdateProgram(program) => {
+                let mut program = *program;
+                keep_text_input_values(&self.widget_state, &mut program.widget_state);
+                *self = program;
+            }
+            SnowcapMessage::Widget(WidgetMessage::Event(id, event)) => {
+                return Command::perform(async move { (id, event) }, |(id, event)| {
+                    SnowcapMessage::Widget(WidgetMessage::Event(id, event))
+                });
+            }
+            SnowcapMessage::Widget(WidgetMessage::TextInputChanged {
+                state_id,
+                widget_id,
+                text,
+            }) => {
+                if let Some(state) = self
+                    .widget_state
+                    .get_mut(&state_id)
+                    .and_then(|state| state.downcast_mut::<TextInputState>())
+                {
+                    state.value.clone_from(&text);
+                }
+
+                if let Some(widget_id) = widget_id {
+                    return Command::perform(async move { (widget_id, text) }, |(id, text)| {
+                        SnowcapMessage::Widget(WidgetMessage::Event(
+                            id,
+                            WidgetEvent::TextChanged(text),
+                        ))
+                    });
+                }
+            }
         }
         Command::none()
     }
 
     fn view(&self) -> Element<'_, Self::Message, Self::Theme, Self::Renderer> {
-        (self.widgets)(&self.widget_state)
+        (self.widgets)(&self.widget_state).map(SnowcapMessage::Widget)
     }
 }
 
@@ -96,10 +251,140 @@ pub fn widget_def_to_fn(def: WidgetDef) -> Option<(WidgetFn, HashMap<u32, Box<dy
     f.map(|f| (f, states))
 }
 
+/// Make the widgets in `def` that fill the space they're given shrink to fit their contents.
+///
+/// Text inputs have no natural width, so theirs are left as is.
+pub fn shrink_fill_lengths(def: &mut WidgetDef) {
+    fn shrink(length: &mut Option<widget::v0alpha1::Length>) {
+        use widget::v0alpha1::length::Strategy;
+
+        if let Some(length) = length {
+            if !matches!(
+                length.strategy,
+                Some(Strategy::Shrink(_) | Strategy::Fixed(_))
+            ) {
+                length.strategy = Some(Strategy::Shrink(()));
+            }
+        }
+    }
+
+    let Some(widget) = def.widget.as_mut() else {
+        return;
+    };
+
+    match widget {
+        widget_def::Widget::Text(text) => {
+            shrink(&mut text.width);
+            shrink(&mut text.height);
+        }
+        widget_def::Widget::Column(column) => {
+            shrink(&mut column.width);
+            shrink(&mut column.height);
+            column.children.iter_mut().for_each(shrink_fill_lengths);
+        }
+        widget_def::Widget::Row(row) => {
+            shrink(&mut row.width);
+            shrink(&mut row.height);
+            row.children.iter_mut().for_each(shrink_fill_lengths);
+        }
+        widget_def::Widget::Scrollable(scrollable) => {
+            shrink(&mut scrollable.width);
+            shrink(&mut scrollable.height);
+            if let Some(child) = scrollable.child.as_deref_mut() {
+                shrink_fill_lengths(child);
+            }
+        }
+        widget_def::Widget::Container(container) => {
+            shrink(&mut container.width);
+            shrink(&mut container.height);
+            if let Some(child) = container.child.as_deref_mut() {
+                shrink_fill_lengths(child);
+            }
+        }
+        widget_def::Widget::Button(button) => {
+            shrink(&mut button.width);
+            shrink(&mut button.height);
+            if let Some(child) = button.child.as_deref_mut() {
+                shrink_fill_lengths(child);
+            }
+        }
+        widget_def::Widget::TextInput(_) => (),
+    }
+}
+
+/// Collect the bounds of the widgets in `def` that are marked interactive,
+/// given the layout of the widgets it was turned into.
+///
+/// Widgets inside of clipping widgets are clipped to their bounds.
+pub fn interactive_bounds(def: &WidgetDef, layout: Layout<'_>, bounds: &mut Vec<Rectangle>) {
+    interactive_bounds_inner(def, layout, None, bounds);
+}
+
+fn interactive_bounds_inner(
+    def: &WidgetDef,
+    layout: Layout<'_>,
+    clip: Option<Rectangle>,
+    bounds: &mut Vec<Rectangle>,
+) {
+    let Some(widget) = def.widget.as_ref() else {
+        return;
+    };
+
+    let widget_bounds = match clip {
+        Some(clip) => match layout.bounds().intersection(&clip) {
+            Some(widget_bounds) => widget_bounds,
+            None => return,
+        },
+        None => layout.bounds(),
+    };
+
+    // Null children are skipped when building the widgets, so they have no layout
+    let mut children = |defs: &[WidgetDef], clip: Option<Rectangle>| {
+        for (child, child_layout) in defs
+            .iter()
+            .filter(|child| child.widget.is_some())
+            .zip(layout.children())
+        {
+            interactive_bounds_inner(child, child_layout, clip, bounds);
+        }
+    };
+
+    match widget {
+        widget_def::Widget::Text(_) => (),
+        widget_def::Widget::Column(column) => {
+            let clip = column.clip().then_some(widget_bounds).or(clip);
+            children(&column.children, clip);
+        }
+        widget_def::Widget::Row(row) => {
+            let clip = row.clip().then_some(widget_bounds).or(clip);
+            children(&row.children, clip);
+        }
+        widget_def::Widget::Scrollable(scrollable) => {
+            if let Some(child) = scrollable.child.as_deref() {
+                children(std::slice::from_ref(child), Some(widget_bounds));
+            }
+        }
+        widget_def::Widget::Button(_) | widget_def::Widget::TextInput(_) => {
+            bounds.push(widget_bounds)
+        }
+        widget_def::Widget::Container(container) => {
+            if container.interactive() {
+                bounds.push(widget_bounds);
+                return;
+            }
+
+            if let Some(child) = container.child.as_deref() {
+                let clip = container.clip().then_some(widget_bounds).or(clip);
+                children(std::slice::from_ref(child), clip);
+            }
+        }
+    }
+}
+
 fn widget_def_to_fn_inner(
     def: WidgetDef,
     current_id: &mut u32,
-    _states: &mut HashMap<u32, Box<dyn Any + Send>>,
+    states: &mut HashMap<u32, Box<dyn Any + Send>>,
 ) -> Option<WidgetFn> {
     let def = def.widget?;
     match def {
@@ -181,7 +466,7 @@ fn widget_def_to_fn_inner(
                 .into_iter()
                 .flat_map(|def| {
                     *current_id += 1;
-                    widget_def_to_fn_inner(def, current_id, _states)
+                    widget_def_to_fn_inner(def, current_id, states)
                 })
                 .collect::<Vec<_>>();
 
@@ -241,7 +526,7 @@ fn widget_def_to_fn_inner(
                 .into_iter()
                 .flat_map(|def| {
                     *current_id += 1;
-                    widget_def_to_fn_inner(def, current_id, _states)
+                    widget_def_to_fn_inner(def, current_id, states)
                 })
                 .collect::<Vec<_>>();
 
@@ -306,7 +591,7 @@ fn widget_def_to_fn_inner(
 
             let child_widget_fn = child.and_then(|def| {
                 *current_id += 1;
-                widget_def_to_fn_inner(*def, current_id, _states)
+                widget_def_to_fn_inner(*def, current_id, states)
             });
 
             let f: WidgetFn = Box::new(move |states| {
@@ -353,11 +638,13 @@ fn widget_def_to_fn_inner(
                 border_radius,
                 border_thickness,
                 border_color,
+
+                interactive: _,
             } = *container_def;
 
             let child_widget_fn = child.and_then(|def| {
                 *current_id += 1;
-                widget_def_to_fn_inner(*def, current_id, _states)
+                widget_def_to_fn_inner(*def, current_id, states)
             });
 
             let f: WidgetFn = Box::new(move |states| {
@@ -450,5 +737,284 @@ fn widget_def_to_fn_inner(
 
             Some(f)
         }
+        widget_def::Widget::Button(button_def) => {
+            let widget::v0alpha1::Button {
+                child,
+                padding,
+                width,
+                height,
+                style,
+                hovered_style,
+                pressed_style,
+                widget_id,
+            } = *button_def;
+
+            let child_widget_fn = child.and_then(|def| {
+                *current_id += 1;
+                widget_def_to_fn_inner(*def, current_id, states)
+            });
+
+            let f: WidgetFn = Box::new(move |states| {
+                let mut button = Button::new(
+                    child_widget_fn
+                        .as_ref()
+                        .map(|child| child(states))
+                        .unwrap_or_else(|| iced::widget::Text::new("NULL").into()),
+                );
+
+                if let Some(width) = width.clone() {
+                    button = button.width(iced::Length::from_api(width));
+                }
+                if let Some(height) = height.clone() {
+                    button = button.height(iced::Length::from_api(height));
+                }
+                if let Some(padding) = padding.clone() {
+                    button = button.padding(iced::Padding::from_api(padding));
+                }
+                if let Some(widget_id) = widget_id {
+                    button = button
+                        .on_press(WidgetMessage::Event(widget_id, WidgetEvent::ButtonPressed));
+                }
+
+                button = button.style(iced::theme::Button::custom(ButtonStyleSheet {
+                    style: style.clone(),
+                    hovered_style: hovered_style.clone(),
+                    pressed_style: pressed_style.clone(),
+                }));
+
+                button.into()
+            });
+
+            Some(f)
+        }
+        widget_def::Widget::TextInput(widget::v0alpha1::TextInput {
+            placeholder,
+            value,
+            font,
+            pixels,
+            padding,
+            width,
+            password,
+            widget_id,
+        }) => {
+            let state_id = *current_id;
+            states.insert(
+                state_id,
+                Box::new(TextInputState {
+                    value: value.clone().unwrap_or_default(),
+                    initial: value,
+                }),
+            );
+
+            let f: WidgetFn = Box::new(move |states| {
+                let value = states
+                    .get(&state_id)
+                    .and_then(|state| state.downcast_ref::<TextInputState>())
+                    .map(|state| state.value.as_str())
+                    .unwrap_or_default();
+
+                let mut text_input =
+                    TextInput::new(placeholder.as_deref().unwrap_or_default(), value)
+                        .on_input(move |text| WidgetMessage::TextInputChanged {
+                            state_id,
+                            widget_id,
+                            text,
+                        })
+                        .secure(password.unwrap_or_default());
+
+                if let Some(widget_id) = widget_id {
+                    text_input = text_input.on_submit(WidgetMessage::Event(
+                        widget_id,
+                        WidgetEvent::TextSubmitted(value.to_string()),
+                    ));
+                }
+                if let Some(font) = font.clone() {
+                    text_input = text_input.font(iced::Font::from_api(font));
+                }
+                if let Some(pixels) = pixels {
+                    text_input = text_input.size(pixels);
+                }
+                if let Some(padding) = padding.clone() {
+                    text_input = text_input.padding(iced::Padding::from_api(padding));
+                }
+                if let Some(width) = width.clone() {
+                    text_input = text_input.width(iced::Length::from_api(width));
+                }
+
+                text_input.into()
+            });
+
+            Some(f)
+        }
+    }
+}
+
+/// Button styles from the API, layered on top of the theme's primary button style.
+struct ButtonStyleSheet {
+    style: Option<widget::v0alpha1::ButtonStyle>,
+    hovered_style: Option<widget::v0alpha1::ButtonStyle>,
+    pressed_style: Option<widget::v0alpha1::ButtonStyle>,
+}
+
+impl ButtonStyleSheet {
+    fn apply(
+        mut appearance: button::Appearance,
+        styles: &[&Option<widget::v0alpha1::ButtonStyle>],
+    ) -> button::Appearance {
+        for style in styles.iter().filter_map(|style| style.as_ref()) {
+            if let Some(text_color) = style.text_color.clone() {
+                appearance.text_color = iced::Color::from_api(text_color);
+            }
+            if let Some(background_color) = style.background_color.clone() {
+                appearance.background = Some(iced::Color::from_api(background_color).into());
+            }
+            if let Some(border_radius) = style.border_radius {
+                appearance.border.radius = border_radius.into();
+            }
+            if let Some(border_thickness) = style.border_thickness {
+                appearance.border.width = border_thickness;
+            }
+            if let Some(border_color) = style.border_color.clone() {
+                appearance.border.color = iced::Color::from_api(border_color);
+            }
+        }
+
+        appearance
+    }
+}
+
+impl button::StyleSheet for ButtonStyleSheet {
+    type Style = iced::Theme;
+
+    fn active(&self, theme: &Self::Style) -> button::Appearance {
+        Self::apply(theme.active(&iced::theme::Button::Primary), &[&self.style])
+    }
+
+    fn hovered(&self, theme: &Self::Style) -> button::Appearance {
+        Self::apply(
+            theme.hovered(&iced::theme::Button::Primary),
+            &[&self.style, &self.hovered_style],
+        )
+    }
+
+    fn pressed(&self, theme: &Self::Style) -> button::Appearance {
+        Self::apply(
+            theme.pressed(&iced::theme::Button::Primary),
+            &[&self.style, &self.hovered_style, &self.pressed_style],
+        )
+    }
+
+    fn disabled(&self, theme: &Self::Style) -> button::Appearance {
+        Self::apply(
+            theme.disabled(&iced::theme::Button::Primary),
+            &[&self.style],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_input(initial: Option<&str>, value: &str) -> Box<dyn Any + Send> {
+        Box::new(TextInputState {
+            initial: initial.map(str::to_string),
+            value: value.to_string(),
+        })
+    }
+
+    fn value_of(states: &HashMap<u32, Box<dyn Any + Send>>, id: u32) -> &str {
+        &states[&id].downcast_ref::<TextInputState>().unwrap().value
+    }
+
+    #[test]
+    fn typed_text_survives_program_updates() {
+        let old = HashMap::from([
+            (0, text_input(None, "typed")),
+            (1, text_input(Some("client"), "edited")),
+        ]);
+        let mut new = HashMap::from([
+            (0, text_input(None, "")),
+            (1, text_input(Some("client"), "client")),
+        ]);
+
+        keep_text_input_values(&old, &mut new);
+
+        assert_eq!(value_of(&new, 0), "typed");
+        assert_eq!(value_of(&new, 1), "edited");
+    }
+
+    #[test]
+    fn client_set_values_replace_typed_text() {
+        let old = HashMap::from([(0, text_input(Some("before"), "typed"))]);
+        let mut new = HashMap::from([(0, text_input(Some("after"), "after"))]);
+
+        keep_text_input_values(&old, &mut new);
+
+        assert_eq!(value_of(&new, 0), "after");
+    }
+
+    #[test]
+    fn other_widget_state_is_not_carried_over() {
+        let old: HashMap<u32, Box<dyn Any + Send>> = HashMap::from([
+            (0, Box::new(5u32) as Box<dyn Any + Send>),
+            (1, text_input(None, "typed")),
+        ]);
+        let mut new = HashMap::from([(0, text_input(None, "")), (2, text_input(None, ""))]);
+
+        keep_text_input_values(&old, &mut new);
+
+        assert_eq!(value_of(&new, 0), "");
+        assert_eq!(value_of(&new, 2), "");
+    }
+
+    #[test]
+    fn fill_lengths_shrink_but_fixed_ones_stay() {
+        use widget::v0alpha1::{length::Strategy, Column, Container, Length, Text};
+
+        let length = |strategy| Some(Length { strategy });
+        let strategy = |length: &Option<Length>| length.clone().unwrap().strategy;
+
+        let mut def = WidgetDef {
+            widget: Some(widget_def::Widget::Container(Box::new(Container {
+                width: length(Some(Strategy::Fill(()))),
+                height: length(None),
+                child: Some(Box::new(WidgetDef {
+                    widget: Some(widget_def::Widget::Column(Column {
+                        width: length(Some(Strategy::FillPortion(2))),
+                        height: length(Some(Strategy::Fixed(30.0))),
+                        children: vec![WidgetDef {
+                            widget: Some(widget_def::Widget::Text(Text {
+                                width: length(Some(Strategy::Fill(()))),
+                                ..Default::default()
+                            })),
+                        }],
+                        ..Default::default()
+                    })),
+                })),
+                ..Default::default()
+            }))),
+        };
+
+        shrink_fill_lengths(&mut def);
+
+        let Some(widget_def::Widget::Container(container)) = &def.widget else {
+            unreachable!();
+        };
+        assert_eq!(strategy(&container.width), Some(Strategy::Shrink(())));
+        assert_eq!(strategy(&container.height), Some(Strategy::Shrink(())));
+
+        let Some(widget_def::Widget::Column(column)) = &container.child.as_ref().unwrap().widget
+        else {
+            unreachable!();
+        };
+        assert_eq!(strategy(&column.width), Some(Strategy::Shrink(())));
+        assert_eq!(strategy(&column.height), Some(Strategy::Fixed(30.0)));
+
+        let Some(widget_def::Widget::Text(text)) = &column.children[0].widget else {
+            unreachable!();
+        };
+        assert_eq!(strategy(&text.width), Some(Strategy::Shrink(())));
+        assert_eq!(text.height, None);
     }
 }